@@ -1,138 +1,1390 @@
-use std::time::Duration;
+mod biquad;
+mod cache;
+mod equalizer;
+mod extractor;
+mod fade;
+mod format;
+mod normalize;
+mod output;
+mod queue;
+mod stretch;
+
+pub use cache::CACHE_SIZE;
+pub use equalizer::{FREQUENCIES, Gains, Preset};
+pub use extractor::{Extractor, Fixtures, Metadata, OnProgress, YtDlp};
+pub use format::{Format, Quality};
+pub use output::{OutputKind, output_devices};
+pub use queue::Queue;
+pub use stretch::{MAX_SPEED, MIN_SPEED};
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use futures::{
-    SinkExt,
-    channel::mpsc::{self},
+    SinkExt, StreamExt,
+    channel::{mpsc, oneshot},
+    future::{self, Either},
 };
+use rodio::source::EmptyCallback;
 use rodio::{Decoder, Source};
-use stream_download::{
-    Settings, StreamDownload,
-    process::{ProcessStreamParams, YtDlpCommand},
-    storage::temp::TempStorageProvider,
-};
-use youtube_dl::YoutubeDl;
+
+use crate::{Error, Track, Video};
+use cache::Cache;
+use equalizer::{Equalize, Equalizer};
+use fade::Fade;
+use normalize::{Normalization, Normalize};
+use output::{OnLost, Output};
+use stretch::{Speed, Stretch};
+
+/// Where a track is read from, a download or a local file.
+pub trait Media: Read + Seek + Send + Sync {}
+
+impl<T> Media for T where T: Read + Seek + Send + Sync {}
+
+type Stream = Decoder<Box<dyn Media>>;
+
+/// How long the playback position may stand still before the track counts as
+/// buffering.
+const STALL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How far past a seek target the track has to be downloaded before seeking
+/// there, so playback doesn't stall right away.
+const SEEK_HEADROOM: Duration = Duration::from_secs(2);
+
+/// How often the output device is checked for coming back after
+/// [`Event::DeviceUnavailable`]. Its stream tells when it goes away.
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the playback position is reported while playing, unless
+/// configured with [`Backend::set_report_interval`].
+const REPORT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How often a preloaded track checks whether it was cancelled once it plays.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The loudness tracks are normalized to unless configured otherwise, the
+/// reference level of YouTube.
+pub const TARGET_LOUDNESS: f32 = -14.0;
 
 #[derive(Debug, Clone)]
-pub struct Backend(mpsc::Sender<Command>);
+pub struct Backend {
+    commands: mpsc::Sender<Command>,
+    cache: Arc<Cache>,
+    extractor: Arc<dyn Extractor>,
+    format: Arc<Mutex<Format>>,
+}
 
 impl Backend {
-    pub async fn load_and_play(mut self, id: String) -> Option<Duration> {
-        let format = "m4a";
-        let url = format!("https://www.youtube.com/watch?v={id}");
-        let output = YoutubeDl::new(&url)
-            .format(format)
-            .extract_audio(true)
-            .run_async()
-            .await
-            .expect("meta data")
-            .into_single_video()
-            .expect("to extract video metadata");
-
-        let size = output.filesize.expect("file size") as u64;
-
-        let cmd = YtDlpCommand::new(url).extract_audio(true).format(format);
-        let reader = StreamDownload::new_process(
-            ProcessStreamParams::new(cmd).unwrap().content_length(size),
-            TempStorageProvider::new(),
-            // Disable cancel_on_drop to ensure no error messages from the process are lost.
-            Settings::default().cancel_on_drop(false),
+    /// Plays `track` right away, it is inserted into the queue after the
+    /// current track.
+    pub async fn load_and_play(mut self, track: Track) -> Result<TrackInfo, Error> {
+        let progress = Arc::new(Progress::default());
+        let format = *self.format.lock().unwrap();
+        let (decoder, info) = open(
+            track.clone(),
+            format,
+            progress.clone(),
+            self.cache.clone(),
+            self.extractor.clone(),
         )
-        .await
-        .unwrap();
+        .await?;
+
+        self.send(Command::PlayStream(track, decoder, progress))
+            .await?;
+
+        Ok(info)
+    }
+
+    /// Inserts `tracks` after the current track and starts playing the first one.
+    pub async fn play_all(mut self, tracks: Vec<Track>) -> Result<(), Error> {
+        self.send(Command::PlayAll(tracks)).await
+    }
+
+    pub async fn enqueue(mut self, track: Track) -> Result<(), Error> {
+        self.send(Command::Enqueue(track)).await
+    }
+
+    pub async fn insert_next(mut self, track: Track) -> Result<(), Error> {
+        self.send(Command::InsertNext(track)).await
+    }
+
+    pub async fn remove(mut self, index: usize) -> Result<(), Error> {
+        self.send(Command::Remove(index)).await
+    }
+
+    pub async fn reorder(mut self, from: usize, to: usize) -> Result<(), Error> {
+        self.send(Command::Reorder { from, to }).await
+    }
+
+    pub async fn jump_to(mut self, index: usize) -> Result<(), Error> {
+        self.send(Command::Jump(index)).await
+    }
+
+    pub async fn skip(mut self) -> Result<(), Error> {
+        self.send(Command::Skip).await
+    }
+
+    pub async fn previous(mut self) -> Result<(), Error> {
+        self.send(Command::Previous).await
+    }
+
+    pub async fn seek_to(mut self, pos: Duration) -> Result<Duration, Error> {
+        self.send(Command::Seek(pos)).await?;
+
+        Ok(pos)
+    }
+
+    pub async fn play(mut self) -> Result<(), Error> {
+        self.send(Command::Play).await
+    }
+
+    pub async fn pause(mut self) -> Result<(), Error> {
+        self.send(Command::Pause).await
+    }
 
-        let reader = reader;
-        let decoder = tokio::task::spawn_blocking(|| rodio::Decoder::new(reader).unwrap())
+    /// Sets the linear gain of the output, `1.0` being unity gain.
+    pub async fn set_volume(mut self, volume: f32) -> Result<(), Error> {
+        self.send(Command::SetVolume(volume)).await
+    }
+
+    pub async fn set_muted(mut self, muted: bool) -> Result<(), Error> {
+        self.send(Command::SetMuted(muted)).await
+    }
+
+    /// Sets how often [`Event::PlaybackPosition`] is reported while playing.
+    /// Nothing is reported while paused or idle.
+    pub async fn set_report_interval(mut self, interval: Duration) -> Result<(), Error> {
+        self.send(Command::SetReportInterval(interval)).await
+    }
+
+    /// Fades consecutive tracks into each other over `duration`, or plays
+    /// them back to back for [`Duration::ZERO`].
+    pub async fn set_crossfade(mut self, duration: Duration) -> Result<(), Error> {
+        self.send(Command::SetCrossfade(duration)).await
+    }
+
+    /// Adjusts the gain of each track toward `target` LUFS if `enabled`.
+    pub async fn set_normalization(mut self, enabled: bool, target: f32) -> Result<(), Error> {
+        self.send(Command::SetNormalization { enabled, target })
             .await
-            .unwrap();
-        let duration = decoder.total_duration();
+    }
 
-        self.0.send(Command::PlayStream(decoder)).await.unwrap();
+    /// Sets the gain of each band of [`FREQUENCIES`] in dB, applied if `enabled`.
+    pub async fn set_equalizer(mut self, enabled: bool, gains: Gains) -> Result<(), Error> {
+        self.send(Command::SetEqualizer { enabled, gains }).await
+    }
 
-        duration
+    /// Plays tracks `speed` times as fast without changing their pitch,
+    /// between [`MIN_SPEED`] and [`MAX_SPEED`].
+    pub async fn set_speed(mut self, speed: f32) -> Result<(), Error> {
+        self.send(Command::SetSpeed(speed)).await
     }
 
-    pub async fn seek_to(mut self, pos: Duration) -> Duration {
-        self.0.send(Command::Seek(pos.clone())).await.unwrap();
+    /// Picks the audio stream of the videos played and downloaded from now on.
+    pub async fn set_format(mut self, format: Format) -> Result<(), Error> {
+        self.send(Command::SetFormat(format)).await
+    }
+
+    /// Keeps `video` in the cache for offline use, see [`Event::DownloadsChanged`].
+    pub async fn download(mut self, video: Video) -> Result<(), Error> {
+        self.send(Command::Download(video)).await
+    }
+
+    /// Lets the cache evict the downloaded video `id` again.
+    pub async fn remove_download(mut self, id: String) -> Result<(), Error> {
+        self.send(Command::RemoveDownload(id)).await
+    }
 
-        pos
+    /// Caps the size of the audio cache in bytes, downloads excluded.
+    pub async fn set_cache_size(mut self, size: u64) -> Result<(), Error> {
+        self.send(Command::SetCacheSize(size)).await
     }
 
-    pub async fn play(mut self) {
-        self.0.send(Command::Play).await.unwrap();
+    /// Moves playback to the output device named `device`, see
+    /// [`output_devices`], or to the default device for `None`.
+    pub async fn set_output_device(mut self, device: Option<String>) -> Result<(), Error> {
+        self.send(Command::SetOutputDevice(device)).await
     }
 
-    pub async fn pause(mut self) {
-        self.0.send(Command::Pause).await.unwrap();
+    /// Fails once the player stopped, e.g. after the events were dropped.
+    async fn send(&mut self, command: Command) -> Result<(), Error> {
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| Error::PlayerStopped)
     }
 }
 
+/// What is known about a track once its stream is set up.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackInfo {
+    pub duration: Option<Duration>,
+    /// The size of the audio file in bytes.
+    pub size: u64,
+}
+
+/// How much of a track has been downloaded, updated by the download task.
+#[derive(Debug, Default)]
+pub struct Progress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+}
+
+impl Progress {
+    /// The downloaded fraction of the track, from 0.0 to 1.0.
+    fn fraction(&self) -> f32 {
+        let total = self.total.load(Ordering::Relaxed);
+
+        if total == 0 {
+            return 0.0;
+        }
+
+        (self.downloaded.load(Ordering::Relaxed) as f32 / total as f32).min(1.0)
+    }
+}
+
+async fn open(
+    track: Track,
+    format: Format,
+    progress: Arc<Progress>,
+    cache: Arc<Cache>,
+    extractor: Arc<dyn Extractor>,
+) -> Result<(Stream, TrackInfo), Error> {
+    let video = match track {
+        Track::Video(video) => video,
+        Track::File(file) => {
+            let (reader, size) = read(&file.path, &progress)?;
+
+            return decode(reader, size).await;
+        }
+    };
+
+    let (reader, size) = match cache.get(&video.id, format) {
+        Some(path) => read(&path, &progress)?,
+        None => download(video, format, progress, cache, &*extractor).await?,
+    };
+
+    decode(reader, size).await
+}
+
+async fn decode(reader: Box<dyn Media>, size: u64) -> Result<(Stream, TrackInfo), Error> {
+    let decoder = tokio::task::spawn_blocking(|| rodio::Decoder::new(reader)).await??;
+    let info = TrackInfo {
+        duration: decoder.total_duration(),
+        size,
+    };
+
+    Ok((decoder, info))
+}
+
+/// Streams the audio of `video`, keeping it in the cache once it is complete.
+async fn download(
+    video: Video,
+    format: Format,
+    progress: Arc<Progress>,
+    cache: Arc<Cache>,
+    extractor: &dyn Extractor,
+) -> Result<(Box<dyn Media>, u64), Error> {
+    let metadata = extractor.resolve(&video, format).await?;
+    let size = metadata.size;
+    progress.total.store(size, Ordering::Relaxed);
+
+    let partial = cache.partial(&video.id, format)?;
+    let is_cached = AtomicBool::new(false);
+
+    let on_progress: OnProgress = {
+        let video = video.clone();
+        let partial = partial.clone();
+
+        Arc::new(move |position| {
+            progress.downloaded.store(position, Ordering::Relaxed);
+
+            if position >= size && !is_cached.swap(true, Ordering::Relaxed) {
+                cache.insert(&partial, &video, format, false);
+            }
+        })
+    };
+
+    let reader = extractor
+        .stream(&video, format, metadata, &partial, on_progress)
+        .await?;
+
+    Ok((reader, size))
+}
+
+/// Opens a local file, which is all there right away.
+fn read(path: &Path, progress: &Progress) -> Result<(Box<dyn Media>, u64), Error> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    progress.total.store(size, Ordering::Relaxed);
+    progress.downloaded.store(size, Ordering::Relaxed);
+
+    Ok((Box::new(BufReader::new(file)), size))
+}
+
 pub enum Command {
-    PlayStream(Decoder<StreamDownload<TempStorageProvider>>),
+    PlayStream(Track, Stream, Arc<Progress>),
+    PlayAll(Vec<Track>),
+    Enqueue(Track),
+    InsertNext(Track),
+    Remove(usize),
+    Reorder {
+        from: usize,
+        to: usize,
+    },
+    Jump(usize),
+    Skip,
+    Previous,
     Play,
     Pause,
     Seek(Duration),
+    SetVolume(f32),
+    SetMuted(bool),
+    SetOutputDevice(Option<String>),
+    SetReportInterval(Duration),
+    SetCrossfade(Duration),
+    SetNormalization {
+        enabled: bool,
+        target: f32,
+    },
+    SetEqualizer {
+        enabled: bool,
+        gains: Gains,
+    },
+    SetSpeed(f32),
+    SetFormat(Format),
+    Download(Video),
+    RemoveDownload(String),
+    SetCacheSize(u64),
+    /// Downloading the video with the given id for offline use finished.
+    Downloaded(String, Result<(), Error>),
+    /// A track the backend started loading itself is ready. Outdated loads,
+    /// e.g. after skipping again, are recognized by their generation.
+    Loaded(usize, Track, Stream),
+    /// Loading a track the backend started itself failed.
+    LoadFailed(usize, Error),
+    /// The output device reported going away, see [`Player::check_device`].
+    CheckDevice,
+    /// The next track of the queue is ready to be appended to the sink.
+    Preloaded(usize, Track, Result<Stream, Error>),
+    /// The sink finished the track with the given id.
+    Ended(usize),
+    /// The track with the given id started to fade out.
+    FadeOut(usize),
 }
 
 #[derive(Debug, Clone)]
 pub enum Event {
     Started(Backend),
     PlaybackPosition(Duration),
+    TrackStarted(Track, Option<Duration>),
+    QueueChanged(Queue),
+    /// The current track played to its end.
+    TrackEnded,
+    /// The track is loading or waiting for data. `progress` is the
+    /// downloaded fraction of the track.
+    Buffering {
+        progress: f32,
+    },
+    /// Playback continues after [`Event::Buffering`].
+    Resumed,
+    /// The downloaded fraction of the current track, reported as it grows.
+    Downloaded(f32),
+    /// Seeking to `position` failed, playback goes on where it was.
+    SeekFailed {
+        position: Duration,
+        error: Error,
+    },
+    Failed(Error),
+    VolumeChanged {
+        volume: f32,
+        muted: bool,
+    },
+    /// Playback moved to another output device, `None` being the default one.
+    /// Also sent once the device is back after [`Event::DeviceUnavailable`].
+    OutputDeviceChanged(Option<String>),
+    /// Switching to `device` failed, playback goes on where it was.
+    OutputDeviceFailed {
+        device: Option<String>,
+        error: Error,
+    },
+    /// The output device can't be opened or went away. Playback stands still
+    /// until it is back, the backend keeps trying.
+    DeviceUnavailable(Error),
+    CrossfadeChanged(Duration),
+    NormalizationChanged {
+        enabled: bool,
+        target: f32,
+    },
+    EqualizerChanged {
+        enabled: bool,
+        gains: Gains,
+    },
+    SpeedChanged(f32),
+    FormatChanged(Format),
+    /// The videos downloaded for offline use changed.
+    DownloadsChanged(Vec<Video>),
+    DownloadFailed {
+        id: String,
+        error: Error,
+    },
+    CacheSizeChanged(u64),
+}
+
+/// How the backend is set up, see [`start_with`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub extractor: Arc<dyn Extractor>,
+    /// Where audio is cached and downloaded to.
+    pub cache_dir: PathBuf,
+    pub output: OutputKind,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            extractor: Arc::new(YtDlp),
+            cache_dir: Cache::default_dir(),
+            output: OutputKind::Device,
+        }
+    }
 }
 
 pub fn start() -> impl futures::Stream<Item = Event> {
+    start_with(Options::default())
+}
+
+/// Starts the backend with other [`Options`], e.g. to test it with
+/// [`Fixtures`] on a [`OutputKind::Null`] output.
+pub fn start_with(options: Options) -> impl futures::Stream<Item = Event> {
     let (event_tx, event_rx) = mpsc::channel(100);
+    // Dropped along with the events, which stops the backend thread.
+    let (watch_tx, watch_rx) = oneshot::channel::<()>();
 
-    std::thread::spawn(|| run(event_tx));
+    std::thread::spawn(|| run(event_tx, watch_tx, options));
 
-    event_rx
+    event_rx.map(move |event| {
+        let _ = &watch_rx;
+        event
+    })
 }
 
 enum State {
-    Starting,
-    Running(rodio::Sink, rodio::OutputStream, mpsc::Receiver<Command>),
+    /// Holds the watch on the events until the player takes it.
+    Starting(oneshot::Sender<()>),
+    Running(Player, mpsc::Receiver<Command>),
+}
+
+/// The audio side of the backend, owned by the backend thread.
+struct Player {
+    sink: rodio::Sink,
+    /// Plays the next track while the current one fades out.
+    fade_sink: rodio::Sink,
+    output: Output,
+    queue: Queue,
+    /// Whether the sink holds a track of the queue, used to notice its end.
+    is_active: bool,
+    is_loading: bool,
+    volume: f32,
+    muted: bool,
+    generation: usize,
+    /// Identifies the current track in the sink, see [`Command::Ended`].
+    track_id: usize,
+    track_id_counter: usize,
+    preload_generation: usize,
+    /// The id and download progress of the next track while it preloads.
+    preloading: Option<(String, Arc<Progress>)>,
+    preloaded: Option<Preloaded>,
+    crossfade: Duration,
+    /// The fade-out length of the current track in milliseconds, see [`Fade`].
+    fade_out: Arc<AtomicU64>,
+    /// Whether the fade sink plays along while the current track fades out.
+    is_crossfading: bool,
+    normalization: Arc<Normalization>,
+    equalizer: Arc<Equalizer>,
+    speed: Arc<Speed>,
+    cache: Arc<Cache>,
+    extractor: Arc<dyn Extractor>,
+    /// Shared with the [`Backend`], which loads tracks as well.
+    format: Arc<Mutex<Format>>,
+    /// The position within the current track in milliseconds, see [`Stretch`].
+    position: Arc<AtomicU64>,
+    progress: Arc<Progress>,
+    /// The last buffering progress reported, `None` while playback flows.
+    buffering: Option<f32>,
+    /// The last download progress reported, see [`Event::Downloaded`].
+    downloaded: Option<f32>,
+    /// The length of the current track, if known.
+    duration: Option<Duration>,
+    /// A seek waiting for the download to get there, see [`Player::seek`].
+    pending_seek: Option<Duration>,
+    last_pos: Duration,
+    last_advance: Instant,
+    report_interval: Duration,
+    next_tick: Instant,
+    next_device_check: Instant,
+    runtime: tokio::runtime::Runtime,
+    commands: mpsc::Sender<Command>,
+    events: mpsc::Sender<Event>,
+    /// Cancelled once the app drops the events, see [`start_with`].
+    watch: oneshot::Sender<()>,
+}
+
+/// The next track of the queue, appended to the sink after the current one
+/// or, when crossfading, to the fade sink.
+struct Preloaded {
+    id: usize,
+    track: Track,
+    duration: Option<Duration>,
+    progress: Arc<Progress>,
+    fade_out: Arc<AtomicU64>,
+    position: Arc<AtomicU64>,
+    /// Skips the track once set, as it can't be taken out of the sink again.
+    cancelled: Arc<AtomicBool>,
+    crossfades: bool,
 }
 
-fn run(mut sender: mpsc::Sender<Event>) {
-    let mut state = State::Starting;
+impl Player {
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::PlayStream(track, decoder, progress) => {
+                let index = self.queue.insert_next([track.clone()]);
+                self.queue.jump(index);
+                self.emit(Event::QueueChanged(self.queue.clone()));
+
+                // Drop tracks the backend is still loading.
+                self.generation += 1;
+                self.is_loading = false;
+                self.progress = progress;
+                self.append(track, decoder);
+            }
+            Command::PlayAll(tracks) => {
+                let index = self.queue.insert_next(tracks);
+                self.queue.jump(index);
+                self.load_current();
+            }
+            Command::Enqueue(track) => {
+                self.queue.enqueue([track]);
+
+                if self.is_idle() {
+                    self.queue.jump(self.queue.len() - 1);
+                    self.load_current();
+                } else {
+                    self.queue_changed();
+                }
+            }
+            Command::InsertNext(track) => {
+                let index = self.queue.insert_next([track]);
+
+                if self.is_idle() {
+                    self.queue.jump(index);
+                    self.load_current();
+                } else {
+                    self.queue_changed();
+                }
+            }
+            Command::Remove(index) => {
+                let was_current = self.queue.current() == Some(index);
+
+                if self.queue.remove(index).is_some() && was_current {
+                    self.load_current();
+                } else {
+                    self.queue_changed();
+                }
+            }
+            Command::Reorder { from, to } => {
+                self.queue.reorder(from, to);
+                self.queue_changed();
+            }
+            Command::Jump(index) => {
+                if self.queue.jump(index).is_some() {
+                    self.load_current();
+                }
+            }
+            Command::Skip => match self.preloaded.take() {
+                Some(preloaded) if self.is_active && !preloaded.crossfades => {
+                    // The preloaded track takes over once the current one ends.
+                    self.preloaded = Some(preloaded);
+                    self.sink.skip_one();
+                }
+                Some(preloaded) if self.is_active => {
+                    self.sink.clear();
+                    self.promote(preloaded);
+                }
+                _ => {
+                    self.queue.next();
+                    self.load_current();
+                }
+            },
+            Command::Previous => {
+                // Like most players, restart the track unless it just began.
+                if self.position() > Duration::from_secs(3) || self.queue.previous().is_none() {
+                    self.seek(Duration::ZERO);
+                } else {
+                    self.load_current();
+                }
+            }
+            Command::Play => {
+                // Replay the current track once it ended.
+                if self.is_idle() && self.queue.current().is_some() {
+                    self.load_current();
+                } else {
+                    self.sink.play();
+                    self.last_advance = Instant::now();
+
+                    if self.is_crossfading {
+                        self.fade_sink.play();
+                    }
+                }
+            }
+            Command::Pause => {
+                self.sink.pause();
+                self.fade_sink.pause();
+                self.report_position();
+            }
+            Command::Seek(pos) => self.seek(pos),
+            Command::SetVolume(volume) => {
+                self.volume = volume.max(0.0);
+                self.apply_volume();
+            }
+            Command::SetMuted(muted) => {
+                self.muted = muted;
+                self.apply_volume();
+            }
+            Command::SetOutputDevice(device) => match self.output.switch(device.as_deref()) {
+                Ok(()) => {
+                    let device = self.output.device().map(str::to_owned);
+                    self.emit(Event::OutputDeviceChanged(device));
+                }
+                Err(err) if !self.output.is_available() => {
+                    self.emit(Event::DeviceUnavailable(err));
+                }
+                Err(error) => self.emit(Event::OutputDeviceFailed { device, error }),
+            },
+            Command::SetCrossfade(duration) => {
+                self.crossfade = duration;
+                self.emit(Event::CrossfadeChanged(duration));
+
+                // Preload the next track again to fade it in, or not.
+                if !self.is_crossfading
+                    && self
+                        .preloaded
+                        .as_ref()
+                        .is_some_and(|preloaded| preloaded.crossfades == duration.is_zero())
+                {
+                    self.drop_preload();
+                    self.preload();
+                }
+            }
+            Command::SetNormalization { enabled, target } => {
+                self.normalization.set(enabled, target);
+                self.emit(Event::NormalizationChanged { enabled, target });
+            }
+            Command::SetEqualizer { enabled, gains } => {
+                self.equalizer.set(enabled, gains);
+                self.emit(Event::EqualizerChanged { enabled, gains });
+            }
+            Command::SetSpeed(speed) => {
+                let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+
+                self.speed.set(speed);
+                self.emit(Event::SpeedChanged(speed));
+            }
+            Command::SetFormat(format) => {
+                *self.format.lock().unwrap() = format;
+                self.emit(Event::FormatChanged(format));
+            }
+            Command::Download(video) => {
+                let cache = self.cache.clone();
+                let extractor = self.extractor.clone();
+                let format = self.format();
+                let mut commands = self.commands.clone();
+
+                self.runtime.spawn(async move {
+                    let result = cache
+                        .download(&video, format, &*extractor)
+                        .await
+                        .map(|_| ());
+                    let _ = commands.send(Command::Downloaded(video.id, result)).await;
+                });
+            }
+            Command::RemoveDownload(id) => {
+                self.cache.remove_download(&id);
+                self.emit(Event::DownloadsChanged(self.cache.downloads()));
+            }
+            Command::SetCacheSize(size) => {
+                self.cache.set_capacity(size);
+                self.emit(Event::CacheSizeChanged(size));
+            }
+            Command::Downloaded(id, result) => match result {
+                Ok(()) => self.emit(Event::DownloadsChanged(self.cache.downloads())),
+                Err(error) => self.emit(Event::DownloadFailed { id, error }),
+            },
+            Command::CheckDevice => self.check_device(),
+            Command::SetReportInterval(interval) => {
+                self.report_interval = interval;
+                self.next_tick = Instant::now() + interval;
+            }
+            Command::Loaded(generation, track, decoder) => {
+                if generation == self.generation {
+                    self.is_loading = false;
+                    self.append(track, decoder);
+                }
+            }
+            Command::LoadFailed(generation, error) => {
+                if generation == self.generation {
+                    self.is_loading = false;
+                    self.emit(Event::Failed(error));
+                    self.advance();
+                }
+            }
+            Command::Preloaded(generation, track, result) => {
+                if generation != self.preload_generation || !self.is_active {
+                    return;
+                }
+
+                let Some((_, progress)) = self.preloading.take() else {
+                    return;
+                };
+
+                match result {
+                    Ok(decoder) => self.append_next(track, decoder, progress),
+                    // Loading it again once it is due reports the error.
+                    Err(err) => tracing::debug!("failed to preload {}: {err}", track.id()),
+                }
+            }
+            Command::Ended(id) => {
+                if id != self.track_id || !self.is_active {
+                    return;
+                }
+
+                self.report_position();
+                self.emit(Event::TrackEnded);
+
+                match self.preloaded.take() {
+                    Some(preloaded) => self.promote(preloaded),
+                    None => {
+                        self.is_active = false;
+                        self.advance();
+                    }
+                }
+            }
+            Command::FadeOut(id) => {
+                let is_next_ready = self
+                    .preloaded
+                    .as_ref()
+                    .is_some_and(|preloaded| preloaded.crossfades);
+
+                if id == self.track_id && is_next_ready {
+                    self.fade_sink.play();
+                    self.is_crossfading = true;
+                }
+            }
+        }
+    }
+
+    /// Stops the sink and loads the current track of the queue, if any.
+    fn load_current(&mut self) {
+        self.emit(Event::QueueChanged(self.queue.clone()));
+
+        self.generation += 1;
+        self.drop_preload();
+        self.sink.clear();
+        self.is_active = false;
+
+        let Some(track) = self.queue.current_track().cloned() else {
+            return;
+        };
+
+        let generation = self.generation;
+        let mut commands = self.commands.clone();
+
+        self.is_loading = true;
+        self.progress = Arc::new(Progress::default());
+        self.buffering = None;
+
+        let load = self.runtime.spawn(open(
+            track.clone(),
+            self.format(),
+            self.progress.clone(),
+            self.cache.clone(),
+            self.extractor.clone(),
+        ));
+
+        self.runtime.spawn(async move {
+            let command = match load.await.map_err(Error::from).and_then(|result| result) {
+                Ok((decoder, _info)) => Command::Loaded(generation, track, decoder),
+                Err(error) => Command::LoadFailed(generation, error),
+            };
+
+            let _ = commands.send(command).await;
+        });
+    }
+
+    /// Loads the next track of the queue, staying on the current one at its end.
+    fn advance(&mut self) {
+        if self.queue.peek_next().is_some() {
+            self.queue.next();
+            self.load_current();
+        } else {
+            self.emit(Event::QueueChanged(self.queue.clone()));
+        }
+    }
+
+    fn append(&mut self, track: Track, decoder: Stream) {
+        let duration = decoder.total_duration();
+        let id = self.next_track_id();
+        let fade_out = Arc::new(AtomicU64::new(0));
+        let position = Arc::new(AtomicU64::new(0));
+
+        self.drop_preload();
+        self.sink.clear();
+        self.sink
+            .append(self.source(decoder, id, None, fade_out.clone(), position.clone()));
+        self.sink.append(self.end_callback(id));
+        self.sink.play();
+        self.track_id = id;
+        self.fade_out = fade_out;
+        self.position = position;
+        self.duration = duration;
+        self.is_active = true;
+        self.reset_stall();
+
+        self.emit(Event::TrackStarted(track, duration));
+        self.preload();
+    }
+
+    /// Appends the preloaded next track. It follows the current one without a
+    /// gap, or fades in on the fade sink while the current one fades out.
+    fn append_next(&mut self, track: Track, decoder: Stream, progress: Arc<Progress>) {
+        let duration = decoder.total_duration();
+        let id = self.next_track_id();
+        let fade_out = Arc::new(AtomicU64::new(0));
+        let position = Arc::new(AtomicU64::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let crossfades = !self.crossfade.is_zero();
+
+        if crossfades {
+            let source = self.source(
+                decoder,
+                id,
+                Some(self.crossfade),
+                fade_out.clone(),
+                position.clone(),
+            );
+
+            self.fade_sink.clear();
+            self.fade_sink.append(source);
+            self.fade_sink.append(self.end_callback(id));
+
+            self.fade_out
+                .store(self.crossfade.as_millis() as u64, Ordering::Relaxed);
+        } else {
+            let source = self
+                .source(decoder, id, None, fade_out.clone(), position.clone())
+                .stoppable()
+                .periodic_access(CANCEL_CHECK_INTERVAL, {
+                    let cancelled = cancelled.clone();
+
+                    move |source| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            source.stop();
+                        }
+                    }
+                });
+
+            self.sink.append(source);
+            self.sink.append(self.end_callback(id));
+        }
+
+        self.preloaded = Some(Preloaded {
+            id,
+            track,
+            duration,
+            progress,
+            fade_out,
+            position,
+            cancelled,
+            crossfades,
+        });
+    }
+
+    /// Makes the preloaded track the current one.
+    fn promote(&mut self, preloaded: Preloaded) {
+        if preloaded.crossfades {
+            std::mem::swap(&mut self.sink, &mut self.fade_sink);
+
+            self.fade_sink.clear();
+            self.sink.play();
+            self.is_crossfading = false;
+        }
+
+        self.queue.next();
+        self.track_id = preloaded.id;
+        self.fade_out = preloaded.fade_out;
+        self.position = preloaded.position;
+        self.duration = preloaded.duration;
+        self.progress = preloaded.progress;
+        self.reset_stall();
+
+        self.emit(Event::QueueChanged(self.queue.clone()));
+        self.emit(Event::TrackStarted(preloaded.track, preloaded.duration));
+        self.preload();
+    }
+
+    fn next_track_id(&mut self) -> usize {
+        self.track_id_counter += 1;
+        self.track_id_counter
+    }
+
+    /// Wraps a decoder in the processing every track goes through.
+    fn source(
+        &self,
+        decoder: Stream,
+        id: usize,
+        fade_in: Option<Duration>,
+        fade_out: Arc<AtomicU64>,
+        position: Arc<AtomicU64>,
+    ) -> Stretch<Fade<Equalize<Normalize<Stream>>>> {
+        let commands = self.commands.clone();
+        let normalized = Normalize::new(decoder, self.normalization.clone());
+
+        // Fades are timed within the track, so stretch them along with it.
+        let faded = Fade::new(
+            Equalize::new(normalized, self.equalizer.clone()),
+            fade_in,
+            fade_out,
+            Box::new(move || {
+                let _ = commands.clone().try_send(Command::FadeOut(id));
+            }),
+        );
+
+        Stretch::new(faded, self.speed.clone(), position)
+    }
+
+    /// Reports the end of the track appended before it, see [`Command::Ended`].
+    fn end_callback(&self, id: usize) -> EmptyCallback<f32> {
+        let commands = self.commands.clone();
+
+        EmptyCallback::new(Box::new(move || {
+            let _ = commands.clone().try_send(Command::Ended(id));
+        }))
+    }
+
+    /// Starts loading the next track of the queue, unless it is loaded already.
+    fn preload(&mut self) {
+        let next = self.queue.peek_next().cloned();
+        let is_next = |id: &str| next.as_ref().is_some_and(|track| track.id() == id);
+
+        if self
+            .preloaded
+            .as_ref()
+            .is_some_and(|preloaded| is_next(&preloaded.track.id()))
+            || self.preloading.as_ref().is_some_and(|(id, _)| is_next(id))
+        {
+            return;
+        }
+
+        self.drop_preload();
+
+        let Some(track) = next.filter(|_| self.is_active) else {
+            return;
+        };
+
+        let generation = self.preload_generation;
+        let progress = Arc::new(Progress::default());
+        let mut commands = self.commands.clone();
+
+        self.preloading = Some((track.id().into_owned(), progress.clone()));
+
+        let load = self.runtime.spawn(open(
+            track.clone(),
+            self.format(),
+            progress,
+            self.cache.clone(),
+            self.extractor.clone(),
+        ));
+
+        self.runtime.spawn(async move {
+            let result = load.await.map_err(Error::from).and_then(|result| result);
+
+            let _ = commands
+                .send(Command::Preloaded(
+                    generation,
+                    track,
+                    result.map(|(decoder, _info)| decoder),
+                ))
+                .await;
+        });
+    }
+
+    /// Forgets the preloaded track and any preload in flight.
+    fn drop_preload(&mut self) {
+        self.preload_generation += 1;
+        self.preloading = None;
+
+        if let Some(preloaded) = self.preloaded.take() {
+            preloaded.cancelled.store(true, Ordering::Relaxed);
+        }
+
+        self.fade_sink.clear();
+        self.fade_out.store(0, Ordering::Relaxed);
+        self.is_crossfading = false;
+    }
+
+    fn queue_changed(&mut self) {
+        self.emit(Event::QueueChanged(self.queue.clone()));
+        self.preload();
+    }
+
+    fn reset_stall(&mut self) {
+        self.buffering = None;
+        self.downloaded = None;
+        self.pending_seek = None;
+        self.last_pos = Duration::ZERO;
+        self.last_advance = Instant::now();
+    }
+
+    /// Whether nothing is playing nor loading.
+    fn is_idle(&self) -> bool {
+        !self.is_active && !self.is_loading
+    }
+
+    /// Whether the loop needs to tick, i.e. a track is loading or playing, or
+    /// a seek waits for the download.
+    fn is_ticking(&self) -> bool {
+        self.is_loading
+            || (self.is_active && (!self.sink.is_paused() || self.pending_seek.is_some()))
+    }
+
+    /// Reports the position and notices playback stalling on the download.
+    fn tick(&mut self) {
+        self.report_downloaded();
+
+        if let Some(target) = self.pending_seek {
+            self.next_tick = Instant::now() + self.report_interval;
+
+            if self.is_downloaded(target) {
+                self.seek(target);
+            } else {
+                self.report_buffering();
+            }
+
+            return;
+        }
+
+        let pos = self.position();
+
+        if self.is_loading {
+            self.report_buffering();
+        } else if self.is_active {
+            if pos != self.last_pos {
+                self.last_advance = Instant::now();
+
+                if self.buffering.take().is_some() {
+                    self.emit(Event::Resumed);
+                }
+            } else if self.last_advance.elapsed() >= STALL_TIMEOUT && self.output.is_available() {
+                self.report_buffering();
+            }
+        }
+
+        self.last_pos = pos;
+        self.next_tick = Instant::now() + self.report_interval;
+
+        let _ = self.events.try_send(Event::PlaybackPosition(pos));
+    }
+
+    /// Seeks within the current track. yt-dlp streams the file from its
+    /// start, so a range beyond the download can't be requested on its own;
+    /// the seek waits for the download to get there instead, as reading ahead
+    /// of it would block the output.
+    fn seek(&mut self, target: Duration) {
+        // Seeking waits for the output to pick it up, which it doesn't
+        // without a device.
+        if !self.output.is_available() {
+            self.pending_seek = None;
+            self.emit(Event::SeekFailed {
+                position: target,
+                error: Error::OutputFailed("no output device".to_string()),
+            });
+
+            return;
+        }
+
+        if self.is_active && !self.is_downloaded(target) {
+            self.pending_seek = Some(target);
+            self.report_buffering();
+
+            return;
+        }
+
+        self.pending_seek = None;
+        let result = self.sink.try_seek(target);
+
+        self.last_pos = self.position();
+        self.last_advance = Instant::now();
+
+        if self.buffering.take().is_some() {
+            self.emit(Event::Resumed);
+        }
+
+        match result {
+            Ok(()) => self.report_position(),
+            Err(error) => self.emit(Event::SeekFailed {
+                position: target,
+                error: error.into(),
+            }),
+        }
+    }
+
+    /// Whether the current track is downloaded a bit past `pos`. The bytes
+    /// needed are estimated from the length, assuming a steady bitrate.
+    fn is_downloaded(&self, pos: Duration) -> bool {
+        let fraction = self.progress.fraction();
+
+        match self.duration {
+            Some(duration) if !duration.is_zero() && fraction < 1.0 => {
+                let needed = (pos + SEEK_HEADROOM).min(duration);
+
+                fraction >= needed.as_secs_f32() / duration.as_secs_f32()
+            }
+            _ => true,
+        }
+    }
+
+    fn report_downloaded(&mut self) {
+        let fraction = self.progress.fraction();
+
+        // Like buffering, only report noticeable progress, but the end in any case.
+        if self
+            .downloaded
+            .is_some_and(|last| last == fraction || (fraction - last < 0.01 && fraction < 1.0))
+        {
+            return;
+        }
+
+        self.downloaded = Some(fraction);
+        let _ = self.events.try_send(Event::Downloaded(fraction));
+    }
+
+    fn report_position(&mut self) {
+        let _ = self
+            .events
+            .try_send(Event::PlaybackPosition(self.position()));
+    }
+
+    /// The position within the current track, which runs ahead of or behind
+    /// the time played unless playing at normal speed.
+    fn position(&self) -> Duration {
+        Duration::from_millis(self.position.load(Ordering::Relaxed))
+    }
+
+    fn format(&self) -> Format {
+        *self.format.lock().unwrap()
+    }
+
+    /// Notices the output device going away and coming back, see
+    /// [`Event::DeviceUnavailable`].
+    fn check_device(&mut self) {
+        self.next_device_check = Instant::now() + DEVICE_CHECK_INTERVAL;
+
+        if self.output.is_available() {
+            if !self.output.is_connected() {
+                let device = self.output.device().unwrap_or("default device").to_owned();
+
+                self.output.detach();
+                self.emit(Event::DeviceUnavailable(Error::OutputFailed(format!(
+                    "{device} disconnected"
+                ))));
+            }
+
+            return;
+        }
+
+        let device = self.output.device().map(str::to_owned);
+
+        if self.output.switch(device.as_deref()).is_ok() {
+            self.last_advance = Instant::now();
+            self.emit(Event::OutputDeviceChanged(device));
+        }
+    }
+
+    /// Does what is due once [`Player::next_command`] gave up waiting.
+    fn wake(&mut self) {
+        let now = Instant::now();
+
+        if !self.output.is_available() && now >= self.next_device_check {
+            self.check_device();
+        }
+
+        if self.is_ticking() && now >= self.next_tick {
+            self.tick();
+        }
+    }
+
+    /// Waits for the next command, `None` once the app dropped the events.
+    /// Gives up at the next tick if the loop is ticking or at the next device
+    /// check while the device is unavailable, returning `Err(())`.
+    fn next_command(
+        &mut self,
+        commands: &mut mpsc::Receiver<Command>,
+    ) -> Result<Option<Command>, ()> {
+        let tick = self.is_ticking().then_some(self.next_tick);
+        let device_check = (!self.output.is_available()).then_some(self.next_device_check);
+        let deadline = tick.into_iter().chain(device_check).min();
+
+        // The player keeps a command sender itself, so the commands never
+        // end on their own.
+        let dropped = self.watch.cancellation();
+        let next = async {
+            match future::select(commands.next(), dropped).await {
+                Either::Left((command, _)) => command,
+                Either::Right(((), _)) => None,
+            }
+        };
+
+        match deadline {
+            Some(deadline) => self
+                .runtime
+                .block_on(tokio::time::timeout_at(deadline.into(), next))
+                .map_err(|_elapsed| ()),
+            // Idle, nothing is due until the next command.
+            None => Ok(self.runtime.block_on(next)),
+        }
+    }
+
+    fn report_buffering(&mut self) {
+        let progress = self.progress.fraction();
+
+        // The loop ticks often, only report noticeable progress.
+        if self
+            .buffering
+            .is_some_and(|last| (progress - last).abs() < 0.01)
+        {
+            return;
+        }
+
+        self.buffering = Some(progress);
+        self.emit(Event::Buffering { progress });
+    }
+
+    fn apply_volume(&mut self) {
+        let volume = if self.muted { 0.0 } else { self.volume };
+
+        self.sink.set_volume(volume);
+        self.fade_sink.set_volume(volume);
+
+        self.emit(Event::VolumeChanged {
+            volume: self.volume,
+            muted: self.muted,
+        });
+    }
+
+    fn emit(&mut self, event: Event) {
+        let _ = futures::executor::block_on(self.events.send(event));
+    }
+}
+
+fn run(mut sender: mpsc::Sender<Event>, watch: oneshot::Sender<()>, options: Options) {
+    let mut state = State::Starting(watch);
 
     loop {
         match state {
-            State::Starting => {
+            State::Starting(watch) => {
                 let (command_tx, command_rx) = mpsc::channel(100);
 
-                let (stream, handle) = rodio::OutputStream::try_default().unwrap();
-                let sink = rodio::Sink::try_new(&handle).unwrap();
-                let backend = Backend(command_tx);
+                let on_lost: OnLost = {
+                    let commands = command_tx.clone();
 
-                sender.try_send(Event::Started(backend)).unwrap();
-                state = State::Running(sink, stream, command_rx);
-            }
-            State::Running(ref sink, ref _stream, ref mut command_rx) => {
-                match command_rx.try_next() {
-                    Ok(Some(command)) => match command {
-                        Command::PlayStream(decoder) => {
-                            sink.clear();
-                            sink.append(decoder);
-                            sink.play();
-                        }
-                        Command::Play => {
-                            sink.play();
-                        }
-                        Command::Pause => {
-                            sink.pause();
-                        }
-                        Command::Seek(pos) => {
-                            sink.try_seek(pos).unwrap();
-                        }
-                    },
-                    Ok(None) => {
-                        dbg!("No one is interested anymore.");
-                        return;
-                    }
-                    Err(_err) => {
-                        let _ = sender.try_send(Event::PlaybackPosition(sink.get_pos()));
-                        std::thread::sleep(Duration::from_millis(20));
+                    Arc::new(move || {
+                        let _ = commands.clone().try_send(Command::CheckDevice);
+                    })
+                };
+
+                let mut unavailable = None;
+                let (output, [sink, fade_sink]) = match options.output {
+                    OutputKind::Device => {
+                        Output::new(None, on_lost.clone()).unwrap_or_else(|err| {
+                            unavailable = Some(err);
+                            Output::unavailable(None, on_lost)
+                        })
                     }
+                    OutputKind::Null { speed } => Output::null(speed, on_lost),
+                };
+                let runtime = tokio::runtime::Runtime::new().unwrap();
+                let cache = Arc::new(Cache::open(options.cache_dir.clone()));
+                let format = Arc::new(Mutex::new(Format::default()));
+                let backend = Backend {
+                    commands: command_tx.clone(),
+                    cache: cache.clone(),
+                    extractor: options.extractor.clone(),
+                    format: format.clone(),
+                };
+
+                sender.try_send(Event::Started(backend)).unwrap();
+                sender
+                    .try_send(Event::DownloadsChanged(cache.downloads()))
+                    .unwrap();
+
+                if let Some(err) = unavailable {
+                    sender.try_send(Event::DeviceUnavailable(err)).unwrap();
+                }
+
+                let player = Player {
+                    sink,
+                    fade_sink,
+                    output,
+                    queue: Queue::default(),
+                    is_active: false,
+                    is_loading: false,
+                    volume: 1.0,
+                    muted: false,
+                    generation: 0,
+                    track_id: 0,
+                    track_id_counter: 0,
+                    preload_generation: 0,
+                    preloading: None,
+                    preloaded: None,
+                    crossfade: Duration::ZERO,
+                    fade_out: Arc::new(AtomicU64::new(0)),
+                    is_crossfading: false,
+                    normalization: Arc::new(Normalization::new(false, TARGET_LOUDNESS)),
+                    equalizer: Arc::new(Equalizer::new()),
+                    speed: Arc::new(Speed::new(1.0)),
+                    cache,
+                    extractor: options.extractor.clone(),
+                    format,
+                    position: Arc::new(AtomicU64::new(0)),
+                    progress: Arc::new(Progress::default()),
+                    buffering: None,
+                    downloaded: None,
+                    duration: None,
+                    pending_seek: None,
+                    last_pos: Duration::ZERO,
+                    last_advance: Instant::now(),
+                    report_interval: REPORT_INTERVAL,
+                    next_tick: Instant::now(),
+                    next_device_check: Instant::now() + DEVICE_CHECK_INTERVAL,
+                    runtime,
+                    commands: command_tx,
+                    events: sender.clone(),
+                    watch,
+                };
+
+                state = State::Running(player, command_rx);
+            }
+            State::Running(ref mut player, ref mut command_rx) => {
+                match player.next_command(command_rx) {
+                    Ok(Some(command)) => player.handle(command),
+                    // No one is interested anymore.
+                    Ok(None) => return,
+                    Err(()) => player.wake(),
                 }
             }
         }
@@ -0,0 +1,121 @@
+use std::f32::consts::PI;
+
+/// Coefficients of a second order IIR filter, normalized by `a0`. The
+/// designs follow the "Audio EQ Cookbook" by Robert Bristow-Johnson.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    pub fn high_shelf(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (cos, alpha) = Self::prewarp(sample_rate, frequency, q);
+        let sqrt_a = 2.0 * a.sqrt() * alpha;
+
+        Self::normalize(
+            a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a),
+            (a + 1.0) - (a - 1.0) * cos + sqrt_a,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - sqrt_a,
+        )
+    }
+
+    pub fn peaking(sample_rate: u32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (cos, alpha) = Self::prewarp(sample_rate, frequency, q);
+
+        Self::normalize(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    pub fn high_pass(sample_rate: u32, frequency: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(sample_rate, frequency, q);
+
+        Self::normalize(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    fn prewarp(sample_rate: u32, frequency: f32, q: f32) -> (f32, f32) {
+        // Keep the filter stable for frequencies above Nyquist.
+        let frequency = frequency.min(sample_rate as f32 * 0.49);
+        let w0 = 2.0 * PI * frequency / sample_rate as f32;
+
+        (w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    fn normalize(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// A biquad filtering interleaved samples, keeping the state of each channel.
+#[derive(Debug, Clone)]
+pub(crate) struct Biquad {
+    coefficients: Coefficients,
+    states: Vec<State>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    pub fn new(coefficients: Coefficients, channels: u16) -> Self {
+        Self {
+            coefficients,
+            states: vec![State::default(); channels as usize],
+        }
+    }
+
+    pub fn process(&mut self, channel: usize, x: f32) -> f32 {
+        if channel >= self.states.len() {
+            self.states.resize(channel + 1, State::default());
+        }
+
+        let Coefficients { b0, b1, b2, a1, a2 } = self.coefficients;
+        let state = &mut self.states[channel];
+
+        let y = b0 * x + b1 * state.x1 + b2 * state.x2 - a1 * state.y1 - a2 * state.y2;
+
+        state.x2 = state.x1;
+        state.x1 = x;
+        state.y2 = state.y1;
+        state.y1 = y;
+
+        y
+    }
+
+    /// Forgets the past samples, e.g. after seeking.
+    pub fn reset(&mut self) {
+        self.states.fill(State::default());
+    }
+}
@@ -0,0 +1,369 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use stream_download::storage::StorageProvider;
+
+use super::{Extractor, Format};
+use crate::config::APP_NAME;
+use crate::{Error, Video};
+
+/// How much audio is kept unless configured otherwise, in bytes.
+pub const CACHE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// The audio files of played and downloaded videos, keyed by video id and
+/// format. Played ones are evicted least recently used first once the cache
+/// grows beyond its capacity, downloaded ones are kept until removed.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    dir: PathBuf,
+    index: Mutex<Index>,
+    /// Tells apart partial files of the same video loading at once.
+    counter: AtomicUsize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    capacity: u64,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    id: String,
+    format: String,
+    title: String,
+    duration_secs: i64,
+    size: u64,
+    last_used: DateTime<Utc>,
+    /// Downloaded for offline use, so never evicted.
+    offline: bool,
+}
+
+impl Cache {
+    /// Opens the cache in `dir`, dropping what is left of downloads
+    /// interrupted last time.
+    pub fn open(dir: PathBuf) -> Self {
+        let index = std::fs::read_to_string(dir.join("index.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| Index {
+                capacity: CACHE_SIZE,
+                entries: Vec::new(),
+            });
+
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                if entry.path().extension().is_some_and(|ext| ext == "part") {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+
+        Self {
+            dir,
+            index: Mutex::new(index),
+            counter: AtomicUsize::new(0),
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/yt-music-player/audio` or its platform equivalent.
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(APP_NAME)
+            .join("audio")
+    }
+
+    /// The cached audio file of the video `id`, if any. Downloads are played
+    /// in whichever format they were downloaded in.
+    pub fn get(&self, id: &str, format: Format) -> Option<PathBuf> {
+        let key = format.key();
+        let mut index = self.index.lock().unwrap();
+
+        let position = index
+            .entries
+            .iter()
+            .position(|entry| entry.id == id && entry.format == key)
+            .or_else(|| {
+                index
+                    .entries
+                    .iter()
+                    .position(|entry| entry.id == id && entry.offline)
+            })?;
+
+        let path = self
+            .dir
+            .join(file_name(id, &index.entries[position].format));
+
+        // The file may have been deleted behind our back.
+        if !path.exists() {
+            index.entries.remove(position);
+            self.save(&index);
+
+            return None;
+        }
+
+        index.entries[position].last_used = Utc::now();
+        self.save(&index);
+
+        Some(path)
+    }
+
+    /// A new partial file to load a stream into, see [`Cache::insert`].
+    pub fn partial(&self, id: &str, format: Format) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+
+        Ok(self
+            .dir
+            .join(format!("{}.{counter}.part", file_name(id, &format.key()))))
+    }
+
+    /// Adds the completely loaded partial file at `partial`, evicting the
+    /// least recently played videos beyond the capacity.
+    pub fn insert(&self, partial: &Path, video: &Video, format: Format, offline: bool) {
+        let format = format.key();
+        let Ok(size) = std::fs::metadata(partial).map(|metadata| metadata.len()) else {
+            return;
+        };
+
+        if let Err(err) = std::fs::rename(partial, self.dir.join(file_name(&video.id, &format))) {
+            tracing::warn!("failed to cache {}: {err}", video.id);
+            return;
+        }
+
+        let mut index = self.index.lock().unwrap();
+
+        let offline = offline
+            || index
+                .find_mut(&video.id, &format)
+                .is_some_and(|entry| entry.offline);
+
+        index
+            .entries
+            .retain(|entry| entry.id != video.id || entry.format != format);
+        index.entries.push(Entry {
+            id: video.id.clone(),
+            format,
+            title: video.title.clone(),
+            duration_secs: video.duration.num_seconds(),
+            size,
+            last_used: Utc::now(),
+            offline,
+        });
+
+        self.evict(&mut index);
+        self.save(&index);
+    }
+
+    /// Downloads `video` for offline use, or keeps it if already cached.
+    /// Returns the audio file.
+    pub async fn download(
+        &self,
+        video: &Video,
+        format: Format,
+        extractor: &dyn Extractor,
+    ) -> Result<PathBuf, Error> {
+        {
+            let mut index = self.index.lock().unwrap();
+
+            if let Some(entry) = index.find_mut(&video.id, &format.key()) {
+                entry.offline = true;
+                let path = self.dir.join(file_name(&entry.id, &entry.format));
+                self.save(&index);
+
+                return Ok(path);
+            }
+        }
+
+        let partial = self.partial(&video.id, format)?;
+        extractor.download(video, format, &partial).await?;
+
+        self.insert(&partial, video, format, true);
+
+        Ok(self.dir.join(file_name(&video.id, &format.key())))
+    }
+
+    /// Makes a downloaded video evictable again.
+    pub fn remove_download(&self, id: &str) {
+        let mut index = self.index.lock().unwrap();
+
+        for entry in index.entries.iter_mut().filter(|entry| entry.id == id) {
+            entry.offline = false;
+        }
+
+        self.evict(&mut index);
+        self.save(&index);
+    }
+
+    pub fn set_capacity(&self, capacity: u64) {
+        let mut index = self.index.lock().unwrap();
+        index.capacity = capacity;
+
+        self.evict(&mut index);
+        self.save(&index);
+    }
+
+    /// The videos downloaded for offline use, by title.
+    pub fn downloads(&self) -> Vec<Video> {
+        let index = self.index.lock().unwrap();
+
+        let mut videos: Vec<_> = index
+            .entries
+            .iter()
+            .filter(|entry| entry.offline)
+            .map(|entry| Video {
+                id: entry.id.clone(),
+                title: entry.title.clone(),
+                duration: chrono::Duration::seconds(entry.duration_secs),
+                channel_title: String::new(),
+                published_at: None,
+                thumbnail: None,
+                tags: Vec::new(),
+                view_count: None,
+                like_count: None,
+            })
+            .collect();
+
+        videos.sort_by(|a, b| a.title.cmp(&b.title));
+        videos.dedup_by(|a, b| a.id == b.id);
+
+        videos
+    }
+
+    fn evict(&self, index: &mut Index) {
+        for entry in index.evict() {
+            let _ = std::fs::remove_file(self.dir.join(file_name(&entry.id, &entry.format)));
+        }
+    }
+
+    fn save(&self, index: &Index) {
+        let result = std::fs::create_dir_all(&self.dir)
+            .map_err(Error::from)
+            .and_then(|()| Ok(serde_json::to_string_pretty(index)?))
+            .and_then(|json| Ok(std::fs::write(self.dir.join("index.json"), json)?));
+
+        if let Err(err) = result {
+            tracing::warn!("failed to save the cache index: {err}");
+        }
+    }
+}
+
+impl Index {
+    fn find_mut(&mut self, id: &str, format: &str) -> Option<&mut Entry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.id == id && entry.format == format)
+    }
+
+    /// Removes the least recently used entries not downloaded for offline
+    /// use until the rest fits the capacity.
+    fn evict(&mut self) -> Vec<Entry> {
+        let capacity = self.capacity;
+        let mut size: u64 = self.entries.iter().map(|entry| entry.size).sum();
+        let mut evicted = Vec::new();
+
+        self.entries.sort_by_key(|entry| entry.last_used);
+
+        self.entries.retain(|entry| {
+            if size <= capacity || entry.offline {
+                return true;
+            }
+
+            size -= entry.size;
+            evicted.push(entry.clone());
+
+            false
+        });
+
+        evicted
+    }
+}
+
+fn file_name(id: &str, format: &str) -> String {
+    format!("{id}.{format}")
+}
+
+/// Writes a stream into a partial file of the [`Cache`].
+pub(crate) struct CacheStorage {
+    path: PathBuf,
+}
+
+impl CacheStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl StorageProvider for CacheStorage {
+    type Reader = File;
+    type Writer = File;
+
+    fn into_reader_writer(self, _content_length: Option<u64>) -> io::Result<(File, File)> {
+        let writer = File::create(&self.path)?;
+        let reader = File::open(&self.path)?;
+
+        Ok((reader, writer))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn entry(id: &str, size: u64, age: i64, offline: bool) -> Entry {
+        Entry {
+            id: id.to_string(),
+            format: "m4a".to_string(),
+            title: id.to_string(),
+            duration_secs: 60,
+            size,
+            last_used: Utc::now() - TimeDelta::seconds(age),
+            offline,
+        }
+    }
+
+    fn ids(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut index = Index {
+            capacity: 250,
+            entries: vec![
+                entry("a", 100, 10, false),
+                entry("b", 100, 30, false),
+                entry("c", 100, 20, false),
+            ],
+        };
+
+        let evicted = index.evict();
+        assert_eq!(ids(&evicted), ["b"]);
+        assert_eq!(ids(&index.entries), ["c", "a"]);
+    }
+
+    #[test]
+    fn keep_downloads() {
+        let mut index = Index {
+            capacity: 150,
+            entries: vec![
+                entry("a", 100, 10, false),
+                entry("b", 100, 30, true),
+                entry("c", 100, 20, false),
+            ],
+        };
+
+        let evicted = index.evict();
+        assert_eq!(ids(&evicted), ["c", "a"]);
+        assert_eq!(ids(&index.entries), ["b"]);
+    }
+}
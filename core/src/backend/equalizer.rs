@@ -0,0 +1,277 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{Sample, Source};
+use serde::{Deserialize, Serialize};
+
+use super::biquad::{Biquad, Coefficients};
+
+/// The center frequencies of the bands in Hz, an octave apart.
+pub const FREQUENCIES: [f32; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// The gain of each band of [`FREQUENCIES`] in dB.
+pub type Gains = [f32; 10];
+
+/// The bandwidth of a band, about an octave.
+const Q: f32 = 1.41;
+
+/// A named set of band gains.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub gains: Gains,
+}
+
+impl Preset {
+    pub fn new(name: impl Into<String>, gains: Gains) -> Self {
+        Self {
+            name: name.into(),
+            gains,
+        }
+    }
+
+    pub fn flat() -> Self {
+        Self::new("Flat", [0.0; 10])
+    }
+
+    /// The presets shipped with the player.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::flat(),
+            Self::new(
+                "Bass boost",
+                [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            ),
+            Self::new(
+                "Treble boost",
+                [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 4.0, 5.0, 6.0],
+            ),
+            Self::new(
+                "Vocal",
+                [-2.0, -2.0, -1.0, 1.0, 3.0, 4.0, 3.0, 1.0, 0.0, -1.0],
+            ),
+            Self::new("Rock", [4.0, 3.0, 2.0, 0.0, -1.0, -1.0, 1.0, 2.0, 3.0, 4.0]),
+            Self::new(
+                "Electronic",
+                [5.0, 4.0, 1.0, 0.0, -2.0, 1.0, 0.0, 1.0, 4.0, 5.0],
+            ),
+            Self::new(
+                "Acoustic",
+                [3.0, 3.0, 2.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0],
+            ),
+        ]
+    }
+}
+
+/// The equalizer settings, shared between the player and the tracks so
+/// changes apply right away.
+#[derive(Debug)]
+pub(crate) struct Equalizer {
+    enabled: AtomicBool,
+    /// The gains as the bits of `f32`s.
+    gains: [AtomicU32; 10],
+    /// Bumped on every change, so tracks know to update their filters.
+    version: AtomicUsize,
+}
+
+impl Equalizer {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            gains: std::array::from_fn(|_| AtomicU32::new(0f32.to_bits())),
+            version: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn set(&self, enabled: bool, gains: Gains) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+
+        for (gain, value) in self.gains.iter().zip(gains) {
+            gain.store(value.to_bits(), Ordering::Relaxed);
+        }
+
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn gains(&self) -> Option<Gains> {
+        self.enabled
+            .load(Ordering::Relaxed)
+            .then(|| std::array::from_fn(|i| f32::from_bits(self.gains[i].load(Ordering::Relaxed))))
+    }
+
+    fn version(&self) -> usize {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+/// Filters a track through a peaking filter per band.
+pub(crate) struct Equalize<S> {
+    inner: S,
+    settings: Arc<Equalizer>,
+    version: Option<usize>,
+    /// The filters of the bands with a gain, `None` if disabled.
+    bands: Option<Vec<Biquad>>,
+    /// Lowers the level by the largest boost to leave headroom.
+    preamp: f32,
+    channel: usize,
+}
+
+impl<S> Equalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    pub fn new(inner: S, settings: Arc<Equalizer>) -> Self {
+        Self {
+            inner,
+            settings,
+            version: None,
+            bands: None,
+            preamp: 1.0,
+            channel: 0,
+        }
+    }
+
+    fn update(&mut self) {
+        let version = self.settings.version();
+
+        if self.version == Some(version) {
+            return;
+        }
+
+        self.version = Some(version);
+
+        let Some(gains) = self.settings.gains() else {
+            self.bands = None;
+            self.preamp = 1.0;
+            return;
+        };
+
+        let sample_rate = self.inner.sample_rate();
+        let channels = self.inner.channels();
+
+        self.bands = Some(
+            FREQUENCIES
+                .iter()
+                .zip(gains)
+                .filter(|(_, gain)| *gain != 0.0)
+                .map(|(&frequency, gain)| {
+                    Biquad::new(
+                        Coefficients::peaking(sample_rate, frequency, Q, gain),
+                        channels,
+                    )
+                })
+                .collect(),
+        );
+
+        let boost = gains.into_iter().fold(0.0, f32::max);
+        self.preamp = 10f32.powf(-boost / 20.0);
+    }
+}
+
+impl<S> Iterator for Equalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?.to_f32();
+
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.inner.channels().max(1) as usize;
+
+        let Some(bands) = &mut self.bands else {
+            return Some(sample);
+        };
+
+        let filtered = bands.iter_mut().fold(sample * self.preamp, |sample, band| {
+            band.process(channel, sample)
+        });
+
+        Some(filtered)
+    }
+}
+
+impl<S> Source for Equalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+
+        for band in self.bands.iter_mut().flatten() {
+            band.reset();
+        }
+
+        self.channel = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rodio::source::SineWave;
+
+    use super::*;
+
+    fn peak(source: impl Iterator<Item = f32>) -> f32 {
+        // Skip the first second while the filters settle.
+        source
+            .skip(48_000)
+            .take(48_000)
+            .fold(0.0, |peak, sample| f32::max(peak, sample.abs()))
+    }
+
+    #[test]
+    fn disabled_is_transparent() {
+        let settings = Arc::new(Equalizer::new());
+        settings.set(false, Preset::builtin()[1].gains);
+
+        let sine = SineWave::new(440.0).amplify(0.5);
+        let equalized = Equalize::new(SineWave::new(440.0).amplify(0.5), settings);
+
+        assert!(sine.zip(equalized).take(48_000).all(|(a, b)| a == b));
+    }
+
+    #[test]
+    fn boost_band() {
+        let settings = Arc::new(Equalizer::new());
+        let mut gains = [0.0; 10];
+        gains[5] = 6.0;
+        settings.set(true, gains);
+
+        let sine = SineWave::new(1000.0).amplify(0.25);
+        let equalized = peak(Equalize::new(sine, settings));
+
+        // The boost of the band is compensated by the preamp.
+        assert!((equalized - 0.25).abs() < 0.01, "{equalized}");
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use stream_download::{
+    Settings, StreamDownload,
+    process::{ProcessStreamParams, YtDlpCommand},
+};
+use youtube_dl::YoutubeDl;
+
+use super::cache::CacheStorage;
+use super::{Format, Media};
+use crate::{Error, Video};
+
+/// Called with the number of bytes of a stream loaded so far.
+pub type OnProgress = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// What is known about the audio of a video before streaming it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    /// The size of the audio file in bytes.
+    pub size: u64,
+}
+
+/// Turns videos into audio streams.
+pub trait Extractor: fmt::Debug + Send + Sync {
+    /// Looks up the audio of `video` in `format`.
+    fn resolve<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+    ) -> BoxFuture<'a, Result<Metadata, Error>>;
+
+    /// Streams the audio of `video` into the file at `partial`, which can be
+    /// read while it loads.
+    fn stream<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+        metadata: Metadata,
+        partial: &'a Path,
+        on_progress: OnProgress,
+    ) -> BoxFuture<'a, Result<Box<dyn Media>, Error>>;
+
+    /// Downloads the audio of `video` to `path` completely.
+    fn download<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+/// Extracts the audio of YouTube videos with yt-dlp.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct YtDlp;
+
+impl YtDlp {
+    fn url(video: &Video) -> String {
+        format!("https://www.youtube.com/watch?v={}", video.id)
+    }
+}
+
+impl Extractor for YtDlp {
+    fn resolve<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+    ) -> BoxFuture<'a, Result<Metadata, Error>> {
+        async move {
+            let output = YoutubeDl::new(Self::url(video))
+                .format(format.selector())
+                .extract_audio(true)
+                .run_async()
+                .await?
+                .into_single_video()
+                .ok_or_else(|| Error::NotFound(video.id.clone()))?;
+
+            let size = output
+                .filesize
+                .ok_or_else(|| Error::MissingFilesize(video.id.clone()))?;
+
+            Ok(Metadata { size: size as u64 })
+        }
+        .boxed()
+    }
+
+    fn stream<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+        metadata: Metadata,
+        partial: &'a Path,
+        on_progress: OnProgress,
+    ) -> BoxFuture<'a, Result<Box<dyn Media>, Error>> {
+        async move {
+            let cmd = YtDlpCommand::new(Self::url(video))
+                .extract_audio(true)
+                .format(format.selector());
+            let params = ProcessStreamParams::new(cmd)
+                .map_err(|err| Error::StreamFailed(err.to_string()))?
+                .content_length(metadata.size);
+
+            let reader = StreamDownload::new_process(
+                params,
+                CacheStorage::new(partial),
+                // Disable cancel_on_drop to ensure no error messages from the process are lost.
+                Settings::default()
+                    .cancel_on_drop(false)
+                    .on_progress(move |_, state, _| on_progress(state.current_position)),
+            )
+            .await
+            .map_err(|err| Error::StreamFailed(err.to_string()))?;
+
+            Ok(Box::new(reader) as Box<dyn Media>)
+        }
+        .boxed()
+    }
+
+    fn download<'a>(
+        &'a self,
+        video: &'a Video,
+        format: Format,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        async move {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            let template = path.file_name().unwrap_or_default().to_string_lossy();
+
+            YoutubeDl::new(Self::url(video))
+                .format(format.selector())
+                .output_template(template)
+                .download_to_async(dir)
+                .await?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Serves audio files from disk in place of videos, so the backend can be
+/// tested without network. The format is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Fixtures {
+    files: HashMap<String, PathBuf>,
+}
+
+impl Fixtures {
+    /// Serves the file at `path` for the video `id`.
+    pub fn with(mut self, id: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.files.insert(id.into(), path.into());
+        self
+    }
+
+    fn file(&self, video: &Video) -> Result<&Path, Error> {
+        self.files
+            .get(&video.id)
+            .map(PathBuf::as_path)
+            .ok_or_else(|| Error::NotFound(video.id.clone()))
+    }
+}
+
+impl Extractor for Fixtures {
+    fn resolve<'a>(
+        &'a self,
+        video: &'a Video,
+        _format: Format,
+    ) -> BoxFuture<'a, Result<Metadata, Error>> {
+        async move {
+            let size = std::fs::metadata(self.file(video)?)?.len();
+
+            Ok(Metadata { size })
+        }
+        .boxed()
+    }
+
+    fn stream<'a>(
+        &'a self,
+        video: &'a Video,
+        _format: Format,
+        metadata: Metadata,
+        partial: &'a Path,
+        on_progress: OnProgress,
+    ) -> BoxFuture<'a, Result<Box<dyn Media>, Error>> {
+        async move {
+            std::fs::copy(self.file(video)?, partial)?;
+
+            // Open it first, as the cache takes the file once it is complete.
+            let file = File::open(partial)?;
+            on_progress(metadata.size);
+
+            Ok(Box::new(file) as Box<dyn Media>)
+        }
+        .boxed()
+    }
+
+    fn download<'a>(
+        &'a self,
+        video: &'a Video,
+        _format: Format,
+        path: &'a Path,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        async move {
+            std::fs::copy(self.file(video)?, path)?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,131 @@
+use std::f32::consts::FRAC_PI_2;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use rodio::Source;
+use rodio::source::SeekError;
+
+/// Applies the equal-power gain curves of a crossfade to a track.
+///
+/// The fade-in is fixed when the track is appended. The length of the
+/// fade-out is shared with the player, which sets it once the next track is
+/// ready to fade in, and `on_fade_out` is called when the fade-out begins.
+pub(crate) struct Fade<S> {
+    inner: S,
+    total: Option<Duration>,
+    fade_in: Option<Duration>,
+    fade_out: Arc<AtomicU64>,
+    on_fade_out: Box<dyn Fn() + Send>,
+    is_fading_out: bool,
+    /// The samples played so far, across all channels.
+    samples: u64,
+}
+
+impl<S> Fade<S>
+where
+    S: Source,
+    S::Item: rodio::Sample,
+{
+    pub fn new(
+        inner: S,
+        fade_in: Option<Duration>,
+        fade_out: Arc<AtomicU64>,
+        on_fade_out: Box<dyn Fn() + Send>,
+    ) -> Self {
+        Self {
+            total: inner.total_duration(),
+            inner,
+            fade_in,
+            fade_out,
+            on_fade_out,
+            is_fading_out: false,
+            samples: 0,
+        }
+    }
+
+    fn elapsed(&self) -> f32 {
+        let rate = self.inner.sample_rate() as f32 * self.inner.channels() as f32;
+
+        self.samples as f32 / rate
+    }
+
+    fn gain(&mut self) -> f32 {
+        let elapsed = self.elapsed();
+        let mut gain = 1.0;
+
+        if let Some(fade_in) = self.fade_in.filter(|fade_in| !fade_in.is_zero()) {
+            let progress = (elapsed / fade_in.as_secs_f32()).min(1.0);
+
+            gain *= (progress * FRAC_PI_2).sin();
+        }
+
+        let fade_out = Duration::from_millis(self.fade_out.load(Ordering::Relaxed));
+
+        if let Some(total) = self.total.filter(|_| !fade_out.is_zero()) {
+            let start = total.saturating_sub(fade_out).as_secs_f32();
+
+            if elapsed >= start {
+                if !self.is_fading_out {
+                    self.is_fading_out = true;
+                    (self.on_fade_out)();
+                }
+
+                let progress = ((elapsed - start) / fade_out.as_secs_f32()).min(1.0);
+
+                gain *= (progress * FRAC_PI_2).cos();
+            }
+        }
+
+        gain
+    }
+}
+
+impl<S> Iterator for Fade<S>
+where
+    S: Source,
+    S::Item: rodio::Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = rodio::Sample::to_f32(self.inner.next()?);
+        let gain = self.gain();
+
+        self.samples += 1;
+
+        Some(sample * gain)
+    }
+}
+
+impl<S> Source for Fade<S>
+where
+    S: Source,
+    S::Item: rodio::Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+
+        let rate = self.inner.sample_rate() as f64 * self.inner.channels() as f64;
+        self.samples = (pos.as_secs_f64() * rate) as u64;
+        self.is_fading_out = false;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The bitrate audio is limited to in data saver mode, in kbit/s.
+const DATA_SAVER_BITRATE: u32 = 64;
+
+/// Which of the audio streams of a video is preferred. Opus streams are
+/// never requested, as symphonia can't decode them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quality {
+    /// The decodable stream with the highest bitrate, taken from a video
+    /// file if there is no audio only stream.
+    #[default]
+    Best,
+    /// Only audio only M4A (AAC) streams.
+    M4a,
+    /// The stream with the lowest bitrate.
+    LowestBandwidth,
+}
+
+impl Quality {
+    pub const ALL: [Self; 3] = [Self::Best, Self::M4a, Self::LowestBandwidth];
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Best => "Best",
+            Self::M4a => "M4A (AAC)",
+            Self::LowestBandwidth => "Lowest bandwidth",
+        })
+    }
+}
+
+/// How the audio of a video is requested from YouTube.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Format {
+    pub quality: Quality,
+    /// Limits the bitrate, e.g. on metered connections.
+    pub data_saver: bool,
+}
+
+impl Format {
+    /// The yt-dlp format selector. Each alternative falls back to the next
+    /// decodable one if a video lacks the preferred stream. In data saver
+    /// mode it falls back to the lowest bitrate if no stream is below the limit.
+    pub fn selector(self) -> String {
+        const LOWEST_M4A: &str = "worstaudio[ext=m4a]/worstaudio[acodec^=mp4a]";
+
+        let saver = format!("bestaudio[ext=m4a][abr<={DATA_SAVER_BITRATE}]");
+
+        match self.quality {
+            Quality::Best if self.data_saver => format!("{saver}/{LOWEST_M4A}/worst[ext=mp4]"),
+            Quality::Best => "bestaudio[ext=m4a]/bestaudio[acodec^=mp4a]/best[ext=mp4]".to_string(),
+            Quality::M4a if self.data_saver => format!("{saver}/{LOWEST_M4A}"),
+            Quality::M4a => "bestaudio[ext=m4a]/bestaudio[acodec^=mp4a]".to_string(),
+            Quality::LowestBandwidth => format!("{LOWEST_M4A}/worst[ext=mp4]"),
+        }
+    }
+
+    /// Tells apart the audio files of a video in the cache.
+    pub(crate) fn key(self) -> String {
+        let quality = match self.quality {
+            Quality::Best => "best",
+            Quality::M4a => "m4a",
+            Quality::LowestBandwidth => "lowest",
+        };
+
+        if self.data_saver {
+            format!("{quality}-saver")
+        } else {
+            quality.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selectors() {
+        let format = |quality, data_saver| Format {
+            quality,
+            data_saver,
+        };
+
+        assert_eq!(
+            format(Quality::Best, false).selector(),
+            "bestaudio[ext=m4a]/bestaudio[acodec^=mp4a]/best[ext=mp4]"
+        );
+        assert_eq!(
+            format(Quality::M4a, false).selector(),
+            "bestaudio[ext=m4a]/bestaudio[acodec^=mp4a]"
+        );
+        assert_eq!(
+            format(Quality::LowestBandwidth, false).selector(),
+            "worstaudio[ext=m4a]/worstaudio[acodec^=mp4a]/worst[ext=mp4]"
+        );
+    }
+
+    #[test]
+    fn data_saver_selectors() {
+        let format = |quality| Format {
+            quality,
+            data_saver: true,
+        };
+
+        assert_eq!(
+            format(Quality::Best).selector(),
+            "bestaudio[ext=m4a][abr<=64]/worstaudio[ext=m4a]/worstaudio[acodec^=mp4a]/worst[ext=mp4]"
+        );
+        assert_eq!(
+            format(Quality::M4a).selector(),
+            "bestaudio[ext=m4a][abr<=64]/worstaudio[ext=m4a]/worstaudio[acodec^=mp4a]"
+        );
+    }
+}
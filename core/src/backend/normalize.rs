@@ -0,0 +1,316 @@
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{Sample, Source};
+
+use super::biquad::{Biquad, Coefficients};
+
+/// How much of a track is analyzed, the gain is fixed afterwards so the
+/// dynamics of the track stay intact.
+const ANALYSIS_WINDOW: Duration = Duration::from_secs(10);
+/// Gating blocks are 400 ms long and overlap by 75%, see ITU-R BS.1770.
+const SUB_BLOCK: Duration = Duration::from_millis(100);
+const SUB_BLOCKS_PER_BLOCK: usize = 4;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+const MAX_BOOST_DB: f32 = 12.0;
+const MAX_CUT_DB: f32 = -24.0;
+/// The highest peak a boost may lead to, leaving room for the peaks between
+/// samples.
+const PEAK_CEILING_DB: f32 = -1.0;
+/// How long the gain takes to settle on a new estimate.
+const SMOOTHING: Duration = Duration::from_millis(500);
+
+/// Whether and where to normalize, shared between the player and the
+/// tracks so changes apply right away.
+#[derive(Debug)]
+pub(crate) struct Normalization {
+    enabled: AtomicBool,
+    /// The target loudness in LUFS, as the bits of an `f32`.
+    target: AtomicU32,
+}
+
+impl Normalization {
+    pub fn new(enabled: bool, target: f32) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            target: AtomicU32::new(target.to_bits()),
+        }
+    }
+
+    pub fn set(&self, enabled: bool, target: f32) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        self.target.store(target.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn target(&self) -> f32 {
+        f32::from_bits(self.target.load(Ordering::Relaxed))
+    }
+}
+
+/// Applies a gain moving a track toward the target loudness. The loudness
+/// is measured following ITU-R BS.1770 over the first seconds of the track.
+pub(crate) struct Normalize<S> {
+    inner: S,
+    settings: Arc<Normalization>,
+    shelf: Biquad,
+    high_pass: Biquad,
+    channels: u16,
+    sample_rate: u32,
+    channel: usize,
+    frames: u64,
+    /// The K-weighted energy of the current sub-block, summed over channels.
+    energy: f64,
+    sub_blocks: VecDeque<f64>,
+    /// The mean square of each gating block analyzed so far.
+    blocks: Vec<f64>,
+    loudness: Option<f32>,
+    /// The highest sample so far.
+    peak: f32,
+    /// The gain keeping the peak below the ceiling, see [`Self::headroom_db`].
+    max_gain: f32,
+    gain: f32,
+}
+
+impl<S> Normalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    pub fn new(inner: S, settings: Arc<Normalization>) -> Self {
+        let channels = inner.channels();
+        let sample_rate = inner.sample_rate();
+
+        Self {
+            shelf: Biquad::new(
+                Coefficients::high_shelf(sample_rate, 1500.0, FRAC_1_SQRT_2, 4.0),
+                channels,
+            ),
+            high_pass: Biquad::new(Coefficients::high_pass(sample_rate, 38.0, 0.5), channels),
+            inner,
+            settings,
+            channels,
+            sample_rate,
+            channel: 0,
+            frames: 0,
+            energy: 0.0,
+            sub_blocks: VecDeque::with_capacity(SUB_BLOCKS_PER_BLOCK),
+            blocks: Vec::new(),
+            loudness: None,
+            peak: 0.0,
+            max_gain: f32::INFINITY,
+            gain: 1.0,
+        }
+    }
+
+    /// The gain applied once settled, in dB.
+    pub fn gain_db(&self) -> f32 {
+        if !self.settings.is_enabled() {
+            return 0.0;
+        }
+
+        self.loudness.map_or(0.0, |loudness| {
+            (self.settings.target() - loudness)
+                .clamp(MAX_CUT_DB, MAX_BOOST_DB)
+                .min(self.headroom_db())
+        })
+    }
+
+    /// How far the track can be boosted before its peak exceeds the ceiling.
+    fn headroom_db(&self) -> f32 {
+        if self.peak <= 0.0 {
+            return MAX_BOOST_DB;
+        }
+
+        (PEAK_CEILING_DB - 20.0 * self.peak.log10()).max(0.0)
+    }
+
+    fn analyze(&mut self, sample: f32) {
+        let weighted = self
+            .high_pass
+            .process(self.channel, self.shelf.process(self.channel, sample));
+
+        self.energy += f64::from(weighted * weighted);
+
+        if self.channel + 1 < self.channels as usize {
+            return;
+        }
+
+        self.frames += 1;
+
+        let sub_block = (SUB_BLOCK.as_secs_f64() * f64::from(self.sample_rate)) as u64;
+
+        if self.frames % sub_block.max(1) != 0 {
+            return;
+        }
+
+        if self.sub_blocks.len() == SUB_BLOCKS_PER_BLOCK {
+            self.sub_blocks.pop_front();
+        }
+
+        self.sub_blocks.push_back(self.energy / sub_block as f64);
+        self.energy = 0.0;
+
+        if self.sub_blocks.len() == SUB_BLOCKS_PER_BLOCK {
+            self.blocks
+                .push(self.sub_blocks.iter().sum::<f64>() / SUB_BLOCKS_PER_BLOCK as f64);
+            self.loudness = integrated_loudness(&self.blocks);
+        }
+    }
+
+    fn is_analyzing(&self) -> bool {
+        self.frames < (ANALYSIS_WINDOW.as_secs_f64() * f64::from(self.sample_rate)) as u64
+    }
+}
+
+impl<S> Iterator for Normalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?.to_f32();
+
+        if sample.abs() > self.peak {
+            self.peak = sample.abs();
+            self.max_gain = 10f32.powf(self.headroom_db() / 20.0).max(1.0);
+        }
+
+        if self.is_analyzing() {
+            self.analyze(sample);
+        }
+
+        if self.channel == 0 {
+            let target = 10f32.powf(self.gain_db() / 20.0);
+            let smoothing = 1.0 / (SMOOTHING.as_secs_f32() * self.sample_rate as f32);
+
+            self.gain += (target - self.gain) * smoothing.min(1.0);
+        }
+
+        // A new peak lowers the gain right away instead of clipping.
+        self.gain = self.gain.min(self.max_gain);
+
+        self.channel = (self.channel + 1) % self.channels.max(1) as usize;
+
+        Some(sample * self.gain)
+    }
+}
+
+impl<S> Source for Normalize<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+
+        self.shelf.reset();
+        self.high_pass.reset();
+        self.channel = 0;
+
+        Ok(())
+    }
+}
+
+/// The gated loudness of the blocks in LUFS, `None` if all are silent.
+fn integrated_loudness(blocks: &[f64]) -> Option<f32> {
+    let lufs = |mean_square: f64| -0.691 + 10.0 * mean_square.log10();
+    let mean = |blocks: &[f64]| blocks.iter().sum::<f64>() / blocks.len() as f64;
+
+    let audible: Vec<f64> = blocks
+        .iter()
+        .copied()
+        .filter(|&block| lufs(block) > ABSOLUTE_GATE)
+        .collect();
+
+    if audible.is_empty() {
+        return None;
+    }
+
+    let threshold = lufs(mean(&audible)) + RELATIVE_GATE;
+    let gated: Vec<f64> = audible
+        .into_iter()
+        .filter(|&block| lufs(block) > threshold)
+        .collect();
+
+    Some(lufs(mean(&gated)) as f32)
+}
+
+#[cfg(test)]
+mod test {
+    use rodio::buffer::SamplesBuffer;
+    use rodio::source::SineWave;
+
+    use super::*;
+
+    #[test]
+    fn normalize_quiet_sine() {
+        let settings = Arc::new(Normalization::new(true, -14.0));
+        let sine = SineWave::new(1000.0).amplify(0.1);
+        let mut normalize = Normalize::new(sine, settings);
+
+        for _ in 0..48_000 * 5 {
+            normalize.next();
+        }
+
+        // A 1 kHz sine at -20 dBFS peak measures about -23 LUFS.
+        let loudness = normalize.loudness.unwrap();
+        assert!((-24.0..-22.0).contains(&loudness), "{loudness}");
+        assert!((8.0..10.0).contains(&normalize.gain_db()));
+    }
+
+    #[test]
+    fn boost_stays_below_peaks() {
+        let settings = Arc::new(Normalization::new(true, -14.0));
+        // A quiet sine with a loud click every second.
+        let samples: Vec<f32> = (0..48_000 * 5)
+            .map(|i| {
+                if i % 48_000 == 1_000 {
+                    0.5
+                } else {
+                    (i as f32 * 1000.0 / 48_000.0 * std::f32::consts::TAU).sin() * 0.05
+                }
+            })
+            .collect();
+        let mut normalize = Normalize::new(SamplesBuffer::new(1, 48_000, samples), settings);
+
+        let ceiling = 10f32.powf(PEAK_CEILING_DB / 20.0);
+        let peak = normalize
+            .by_ref()
+            .fold(0f32, |peak, sample| peak.max(sample.abs()));
+
+        assert!(peak <= ceiling + 1e-4, "{peak}");
+        assert!(normalize.gain_db() > 0.0);
+    }
+
+    #[test]
+    fn silence_is_left_alone() {
+        assert_eq!(integrated_loudness(&[0.0; 10]), None);
+    }
+}
@@ -0,0 +1,474 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{self, FromSample, Sample, SampleFormat, SizedSample, StreamError};
+use rodio::dynamic_mixer::{self, DynamicMixer};
+use rodio::queue::SourcesQueueOutput;
+use rodio::{Sink, Source};
+
+use crate::Error;
+
+type Slot = Arc<Mutex<Option<SourcesQueueOutput<f32>>>>;
+
+/// Called from the audio thread once the device went away, see
+/// [`Output::is_connected`].
+pub(crate) type OnLost = Arc<dyn Fn() + Send + Sync>;
+
+/// How often the null output consumes samples.
+const NULL_TICK: Duration = Duration::from_millis(5);
+
+/// Where the backend plays, see [`super::Options`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputKind {
+    /// An output device, see [`super::Backend::set_output_device`].
+    Device,
+    /// Plays nowhere, consuming the samples `speed` times as fast as a
+    /// device would, e.g. to test playback without a sound card.
+    Null { speed: f32 },
+}
+
+/// Lists the names of the available output devices.
+pub fn output_devices() -> Result<Vec<String>, Error> {
+    let devices = rodio::cpal::default_host()
+        .output_devices()
+        .map_err(|err| Error::OutputFailed(err.to_string()))?;
+
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+/// Plays the queues of sinks on an output device. The device can be switched
+/// while playing, the sinks and thus the positions stay untouched.
+pub(crate) struct Output {
+    target: Target,
+    slots: Vec<Slot>,
+    device: Option<String>,
+    on_lost: OnLost,
+}
+
+impl Output {
+    /// Opens `device`, or the default device for `None`, and creates `N`
+    /// sinks playing on it at the same time.
+    pub fn new<const N: usize>(
+        device: Option<&str>,
+        on_lost: OnLost,
+    ) -> Result<(Self, [Sink; N]), Error> {
+        let (sinks, queues) = sinks();
+        let slots = slots(queues);
+        let stream = DeviceStream::open(&find(device)?, &slots, on_lost.clone())?;
+
+        let output = Self {
+            target: Target::Device(stream),
+            slots,
+            device: device.map(str::to_owned),
+            on_lost,
+        };
+
+        Ok((output, sinks))
+    }
+
+    /// Creates `N` sinks playing nowhere, see [`OutputKind::Null`]. Switching
+    /// to a device moves them to the device.
+    pub fn null<const N: usize>(speed: f32, on_lost: OnLost) -> (Self, [Sink; N]) {
+        let (sinks, queues) = sinks();
+        let slots = slots(queues);
+
+        let output = Self {
+            target: Target::Null(NullOutput::start(slots.clone(), speed)),
+            slots,
+            device: None,
+            on_lost,
+        };
+
+        (output, sinks)
+    }
+
+    /// Creates `N` sinks for `device` while it can't be opened. They stand
+    /// still until [`Output::switch`] succeeds.
+    pub fn unavailable<const N: usize>(device: Option<&str>, on_lost: OnLost) -> (Self, [Sink; N]) {
+        let (sinks, queues) = sinks();
+
+        let output = Self {
+            target: Target::None,
+            slots: slots(queues),
+            device: device.map(str::to_owned),
+            on_lost,
+        };
+
+        (output, sinks)
+    }
+
+    /// The device played on, or to be played on once available.
+    pub fn device(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+
+    pub fn is_available(&self) -> bool {
+        !matches!(self.target, Target::None)
+    }
+
+    /// Whether the device is still there, e.g. not unplugged. Its stream
+    /// tells once it went away, calling [`OnLost`] as well.
+    pub fn is_connected(&self) -> bool {
+        match &self.target {
+            Target::Device(stream) => !stream.is_lost.load(Ordering::Relaxed),
+            Target::Null(_) => true,
+            Target::None => false,
+        }
+    }
+
+    /// Stops playing on the device, e.g. as it went away, see
+    /// [`Output::unavailable`].
+    pub fn detach(&mut self) {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| Arc::new(Mutex::new(slot.lock().unwrap().take())))
+            .collect();
+
+        *self = Self {
+            target: Target::None,
+            slots,
+            device: self.device.take(),
+            on_lost: self.on_lost.clone(),
+        };
+    }
+
+    /// Moves playback to `device`, or the default device for `None`. While
+    /// unavailable, `device` is waited for even if it can't be opened yet.
+    pub fn switch(&mut self, device: Option<&str>) -> Result<(), Error> {
+        let found = match find(device) {
+            Ok(found) => found,
+            Err(err) => {
+                if !self.is_available() {
+                    self.device = device.map(str::to_owned);
+                }
+
+                return Err(err);
+            }
+        };
+
+        let slots = slots(self.slots.iter().map(|slot| slot.lock().unwrap().take()));
+
+        match DeviceStream::open(&found, &slots, self.on_lost.clone()) {
+            Ok(stream) => {
+                *self = Self {
+                    target: Target::Device(stream),
+                    slots,
+                    device: device.map(str::to_owned),
+                    on_lost: self.on_lost.clone(),
+                };
+
+                Ok(())
+            }
+            Err(err) => {
+                for (slot, queue) in self.slots.iter().zip(&slots) {
+                    *slot.lock().unwrap() = queue.lock().unwrap().take();
+                }
+
+                Err(err)
+            }
+        }
+    }
+}
+
+fn sinks<const N: usize>() -> ([Sink; N], Vec<SourcesQueueOutput<f32>>) {
+    let (sinks, queues): (Vec<_>, Vec<_>) = (0..N).map(|_| Sink::new_idle()).unzip();
+
+    let Ok(sinks) = sinks.try_into() else {
+        unreachable!("one sink is created per slot");
+    };
+
+    (sinks, queues)
+}
+
+fn slots(
+    queues: impl IntoIterator<Item = impl Into<Option<SourcesQueueOutput<f32>>>>,
+) -> Vec<Slot> {
+    queues
+        .into_iter()
+        .map(|queue| Arc::new(Mutex::new(queue.into())))
+        .collect()
+}
+
+/// Looks up `device`, or the default device for `None`.
+fn find(device: Option<&str>) -> Result<cpal::Device, Error> {
+    let host = cpal::default_host();
+
+    match device {
+        Some(name) => host
+            .output_devices()
+            .map_err(|err| Error::OutputFailed(err.to_string()))?
+            .find(|device| device.name().is_ok_and(|device| device == name))
+            .ok_or_else(|| Error::NotFound(name.to_owned())),
+        None => host
+            .default_output_device()
+            .ok_or_else(|| Error::OutputFailed("no default output device".to_string())),
+    }
+}
+
+/// What the sinks of an [`Output`] play on.
+enum Target {
+    Device(DeviceStream),
+    Null(NullOutput),
+    /// Nothing, as the device is unavailable.
+    None,
+}
+
+/// Mixes the slots into a stream on an output device. Unlike a rodio
+/// `OutputStream`, it notices the device going away.
+struct DeviceStream {
+    _stream: cpal::Stream,
+    /// Set by the stream once the device went away.
+    is_lost: Arc<AtomicBool>,
+}
+
+impl DeviceStream {
+    fn open(device: &cpal::Device, slots: &[Slot], on_lost: OnLost) -> Result<Self, Error> {
+        let config = device
+            .default_output_config()
+            .map_err(|err| Error::OutputFailed(err.to_string()))?;
+        let (controller, mixer) =
+            dynamic_mixer::mixer::<f32>(config.channels(), config.sample_rate().0);
+
+        for slot in slots {
+            controller.add(Detachable::new(slot.clone()));
+        }
+
+        let is_lost = Arc::new(AtomicBool::new(false));
+        let on_error = {
+            let is_lost = is_lost.clone();
+
+            // Other errors, e.g. underruns, pass.
+            move |err| {
+                if let StreamError::DeviceNotAvailable = err {
+                    is_lost.store(true, Ordering::Relaxed);
+                    on_lost();
+                }
+            }
+        };
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build::<f32>(device, &config.config(), mixer, on_error),
+            SampleFormat::I16 => build::<i16>(device, &config.config(), mixer, on_error),
+            SampleFormat::U16 => build::<u16>(device, &config.config(), mixer, on_error),
+            format => {
+                return Err(Error::OutputFailed(format!(
+                    "unsupported sample format {format:?}"
+                )));
+            }
+        }
+        .map_err(|err| Error::OutputFailed(err.to_string()))?;
+
+        stream
+            .play()
+            .map_err(|err| Error::OutputFailed(err.to_string()))?;
+
+        Ok(Self {
+            _stream: stream,
+            is_lost,
+        })
+    }
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut mixer: DynamicMixer<f32>,
+    on_error: impl FnMut(StreamError) + Send + 'static,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for sample in data {
+                *sample = T::from_sample(mixer.next().unwrap_or(0.0));
+            }
+        },
+        on_error,
+        None,
+    )
+}
+
+/// Consumes the queues of the slots in a thread of its own at the pace of a
+/// device, as long as it lives.
+struct NullOutput {
+    is_running: Arc<AtomicBool>,
+}
+
+impl NullOutput {
+    fn start(slots: Vec<Slot>, speed: f32) -> Self {
+        let is_running = Arc::new(AtomicBool::new(true));
+        let running = is_running.clone();
+
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            // The time consumed of each slot in seconds.
+            let mut consumed = vec![0.0; slots.len()];
+
+            while running.load(Ordering::Relaxed) {
+                let due = start.elapsed().as_secs_f64() * f64::from(speed);
+
+                for (slot, consumed) in slots.iter().zip(&mut consumed) {
+                    let mut slot = slot.lock().unwrap();
+                    let Some(queue) = slot.as_mut() else {
+                        *consumed = due;
+                        continue;
+                    };
+
+                    let sample_rate = f64::from(queue.sample_rate());
+                    let frames = ((due - *consumed) * sample_rate).floor();
+
+                    for _ in 0..frames as usize * usize::from(queue.channels()) {
+                        queue.next();
+                    }
+
+                    *consumed += frames / sample_rate;
+                }
+
+                std::thread::sleep(NULL_TICK);
+            }
+        });
+
+        Self { is_running }
+    }
+}
+
+impl Drop for NullOutput {
+    fn drop(&mut self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The most samples a [`Detachable`] takes from its queue at once.
+const CHUNK_LEN: usize = 512;
+
+/// Forwards the sink's queue to the mixer of an output stream until the
+/// queue is taken out of the slot, it plays silence from then on. The slot
+/// is locked once per chunk of samples rather than for every sample, as
+/// this runs in the audio callback.
+struct Detachable {
+    slot: Slot,
+    chunk: Vec<f32>,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Detachable {
+    fn new(slot: Slot) -> Self {
+        let mut detachable = Self {
+            slot,
+            chunk: Vec::with_capacity(CHUNK_LEN),
+            position: 0,
+            channels: 2,
+            sample_rate: 44_100,
+        };
+        detachable.refill();
+
+        detachable
+    }
+
+    /// Takes the next chunk from the queue. A chunk ends with the current
+    /// frame, so its samples share the channels and sample rate.
+    fn refill(&mut self) {
+        self.chunk.clear();
+        self.position = 0;
+
+        match self.slot.lock().unwrap().as_mut() {
+            Some(queue) => {
+                self.channels = queue.channels();
+                self.sample_rate = queue.sample_rate();
+
+                let len = queue
+                    .current_frame_len()
+                    .unwrap_or(CHUNK_LEN)
+                    .clamp(1, CHUNK_LEN);
+                self.chunk.extend(queue.by_ref().take(len));
+            }
+            None => {
+                let len = CHUNK_LEN - CHUNK_LEN % usize::from(self.channels);
+                self.chunk.resize(len, 0.0);
+            }
+        }
+
+        // The queue plays silence once empty, but don't rely on it.
+        if self.chunk.is_empty() {
+            self.chunk.resize(usize::from(self.channels), 0.0);
+        }
+    }
+}
+
+impl Iterator for Detachable {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.chunk[self.position];
+        self.position += 1;
+
+        // Refill right away, so the format reported is the next sample's.
+        if self.position == self.chunk.len() {
+            self.refill();
+        }
+
+        Some(sample)
+    }
+}
+
+impl Source for Detachable {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.chunk.len() - self.position)
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rodio::source::SineWave;
+
+    use super::*;
+
+    #[test]
+    fn null_output_consumes_at_speed() {
+        let speed = 20.0;
+        let length = Duration::from_secs(2);
+        let started = Instant::now();
+
+        let (_output, [sink]) = Output::null::<1>(speed, Arc::new(|| {}));
+        sink.append(SineWave::new(440.0).take_duration(length));
+
+        // However the threads are scheduled, playback never runs ahead of the speed.
+        while !sink.empty() {
+            assert!(sink.get_pos() <= started.elapsed().mul_f32(speed));
+            assert!(started.elapsed() < length, "not faster than real time");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(started.elapsed() >= length.div_f32(speed));
+    }
+
+    #[test]
+    fn unavailable_output_stands_still() {
+        let (mut output, [sink]) = Output::null::<1>(20.0, Arc::new(|| {}));
+        output.detach();
+        assert!(!output.is_available());
+
+        sink.append(SineWave::new(440.0).take_duration(Duration::from_secs(1)));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!sink.empty());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::Track;
+
+/// The tracks lined up for playback and the one currently playing.
+#[derive(Debug, Clone, Default)]
+pub struct Queue {
+    tracks: Vec<Track>,
+    current: Option<usize>,
+}
+
+impl Queue {
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current_track(&self) -> Option<&Track> {
+        self.current.and_then(|index| self.tracks.get(index))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn enqueue(&mut self, tracks: impl IntoIterator<Item = Track>) {
+        self.tracks.extend(tracks);
+    }
+
+    /// Inserts `tracks` right after the current track, or at the end if
+    /// nothing is playing. Returns the index of the first inserted track.
+    pub fn insert_next(&mut self, tracks: impl IntoIterator<Item = Track>) -> usize {
+        let index = self
+            .current
+            .map_or(self.tracks.len(), |current| current + 1);
+
+        self.tracks.splice(index..index, tracks);
+
+        index
+    }
+
+    /// Removes the track at `index`. If it was the current track, the track
+    /// following it becomes the current one.
+    pub fn remove(&mut self, index: usize) -> Option<Track> {
+        if index >= self.tracks.len() {
+            return None;
+        }
+
+        let track = self.tracks.remove(index);
+
+        self.current = match self.current {
+            Some(current) if index < current => Some(current - 1),
+            Some(current) if index == current && current >= self.tracks.len() => None,
+            current => current,
+        };
+
+        Some(track)
+    }
+
+    /// Moves the track at `from` to `to`, keeping the current track current.
+    pub fn reorder(&mut self, from: usize, to: usize) {
+        if from >= self.tracks.len() || to >= self.tracks.len() || from == to {
+            return;
+        }
+
+        let track = self.tracks.remove(from);
+        self.tracks.insert(to, track);
+
+        self.current = self.current.map(|current| {
+            if current == from {
+                to
+            } else if from < current && current <= to {
+                current - 1
+            } else if to <= current && current < from {
+                current + 1
+            } else {
+                current
+            }
+        });
+    }
+
+    pub fn jump(&mut self, index: usize) -> Option<&Track> {
+        if index >= self.tracks.len() {
+            return None;
+        }
+
+        self.current = Some(index);
+        self.current_track()
+    }
+
+    /// Advances to the next track. Returns `None` at the end of the queue.
+    pub fn next(&mut self) -> Option<&Track> {
+        let index = self.current.map_or(0, |current| current + 1);
+
+        if index >= self.tracks.len() {
+            self.current = None;
+            return None;
+        }
+
+        self.jump(index)
+    }
+
+    /// Peeks at the track [`Queue::next`] would advance to.
+    pub fn peek_next(&self) -> Option<&Track> {
+        self.tracks
+            .get(self.current.map_or(0, |current| current + 1))
+    }
+
+    pub fn previous(&mut self) -> Option<&Track> {
+        let index = self.current?.checked_sub(1)?;
+
+        self.jump(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::Video;
+
+    fn video(id: &str) -> Track {
+        Track::Video(Video {
+            id: id.to_string(),
+            title: id.to_string(),
+            duration: Duration::seconds(60),
+            channel_title: String::new(),
+            published_at: None,
+            thumbnail: None,
+            tags: Vec::new(),
+            view_count: None,
+            like_count: None,
+        })
+    }
+
+    fn ids(queue: &Queue) -> Vec<Cow<'_, str>> {
+        queue.tracks().iter().map(Track::id).collect()
+    }
+
+    #[test]
+    fn advance() {
+        let mut queue = Queue::default();
+        queue.enqueue([video("a"), video("b")]);
+
+        assert_eq!(queue.next().map(Track::id).as_deref(), Some("a"));
+        assert_eq!(queue.next().map(Track::id).as_deref(), Some("b"));
+        assert_eq!(queue.previous().map(Track::id).as_deref(), Some("a"));
+        assert!(queue.previous().is_none());
+        assert_eq!(queue.current(), Some(0));
+
+        queue.jump(1);
+        assert!(queue.next().is_none());
+        assert_eq!(queue.current(), None);
+    }
+
+    #[test]
+    fn insert_next() {
+        let mut queue = Queue::default();
+        queue.enqueue([video("a"), video("b")]);
+        queue.jump(0);
+
+        let index = queue.insert_next([video("c"), video("d")]);
+        assert_eq!(index, 1);
+        assert_eq!(ids(&queue), ["a", "c", "d", "b"]);
+        assert_eq!(queue.current(), Some(0));
+    }
+
+    #[test]
+    fn remove() {
+        let mut queue = Queue::default();
+        queue.enqueue([video("a"), video("b"), video("c")]);
+        queue.jump(1);
+
+        queue.remove(0);
+        assert_eq!(ids(&queue), ["b", "c"]);
+        assert_eq!(queue.current(), Some(0));
+
+        queue.remove(0);
+        assert_eq!(queue.current_track().map(Track::id).as_deref(), Some("c"));
+
+        queue.remove(0);
+        assert_eq!(queue.current(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn reorder() {
+        let mut queue = Queue::default();
+        queue.enqueue([video("a"), video("b"), video("c")]);
+        queue.jump(1);
+
+        queue.reorder(0, 2);
+        assert_eq!(ids(&queue), ["b", "c", "a"]);
+        assert_eq!(queue.current(), Some(0));
+
+        queue.reorder(0, 1);
+        assert_eq!(ids(&queue), ["c", "b", "a"]);
+        assert_eq!(queue.current(), Some(1));
+
+        queue.reorder(2, 0);
+        assert_eq!(ids(&queue), ["a", "c", "b"]);
+        assert_eq!(queue.current(), Some(2));
+    }
+}
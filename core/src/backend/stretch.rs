@@ -0,0 +1,395 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::{Sample, Source};
+
+/// The slowest playback speed.
+pub const MIN_SPEED: f32 = 0.5;
+/// The fastest playback speed.
+pub const MAX_SPEED: f32 = 3.0;
+
+/// The length of a grain, a few periods of a voice.
+const GRAIN: Duration = Duration::from_millis(30);
+/// How far a grain may be moved to line up with the previous one.
+const TOLERANCE: Duration = Duration::from_millis(10);
+/// The frames skipped between the offsets and points compared when first
+/// looking for a grain's position, the best match is refined afterwards.
+const COARSE_STEP: usize = 4;
+
+/// The playback speed, shared between the player and the tracks so changes
+/// apply right away.
+#[derive(Debug)]
+pub(crate) struct Speed(AtomicU32);
+
+impl Speed {
+    pub fn new(speed: f32) -> Self {
+        Self(AtomicU32::new(speed.to_bits()))
+    }
+
+    pub fn set(&self, speed: f32) {
+        self.0.store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Changes the tempo of a track without changing its pitch (WSOLA).
+///
+/// Overlapping grains are taken from the track further apart or closer
+/// together than they are played, each moved slightly to line up with the
+/// waveform of the grain before. As the sink only knows the time played, the
+/// position within the track is shared in milliseconds.
+pub(crate) struct Stretch<S> {
+    inner: S,
+    speed: Arc<Speed>,
+    position: Arc<AtomicU64>,
+    channels: usize,
+    sample_rate: u32,
+    /// The frames per grain, grains are played half of it apart.
+    grain: usize,
+    tolerance: usize,
+    window: Vec<f32>,
+    /// The interleaved samples of the track from frame `input_start` on.
+    input: VecDeque<f32>,
+    input_start: usize,
+    is_exhausted: bool,
+    /// Where the next grain starts before lining it up, in frames.
+    analysis: f64,
+    /// Where the track continues after the previous grain, `None` before the
+    /// first one.
+    continuation: Option<usize>,
+    /// The overlapped grains, the first half of which is complete.
+    output: Vec<f32>,
+    /// The next sample of `output` to play.
+    cursor: usize,
+    /// The mono mix of the frames after the previous grain and of those a
+    /// grain may be moved over, kept to not allocate for every grain.
+    reference: Vec<f32>,
+    search: Vec<f32>,
+}
+
+impl<S> Stretch<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    pub fn new(inner: S, speed: Arc<Speed>, position: Arc<AtomicU64>) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        let sample_rate = inner.sample_rate();
+        let frames = |duration: Duration| (duration.as_secs_f64() * sample_rate as f64) as usize;
+
+        // Hann windows half a grain apart add up to one.
+        let grain = (frames(GRAIN) / 2).max(1) * 2;
+        let tolerance = frames(TOLERANCE);
+        let window = (0..grain)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / grain as f32).cos())
+            .collect();
+
+        Self {
+            inner,
+            speed,
+            position,
+            channels,
+            sample_rate,
+            grain,
+            tolerance,
+            window,
+            input: VecDeque::new(),
+            input_start: 0,
+            is_exhausted: false,
+            analysis: 0.0,
+            continuation: None,
+            output: vec![0.0; grain * channels],
+            cursor: grain / 2 * channels,
+            reference: Vec::with_capacity(grain / 2),
+            search: Vec::with_capacity(2 * tolerance + grain / 2 + 1),
+        }
+    }
+
+    fn hop(&self) -> usize {
+        self.grain / 2
+    }
+
+    /// The frame after the last one read from the track.
+    fn input_end(&self) -> usize {
+        self.input_start + self.input.len() / self.channels
+    }
+
+    /// Reads the track up to `frame`, unless it ends before.
+    fn fill(&mut self, frame: usize) {
+        let samples = frame.saturating_sub(self.input_start) * self.channels;
+
+        while !self.is_exhausted && self.input.len() < samples {
+            match self.inner.next() {
+                Some(sample) => self.input.push_back(sample.to_f32()),
+                None => self.is_exhausted = true,
+            }
+        }
+    }
+
+    /// The sample at `frame`, silence past the end of the track.
+    fn sample(&self, frame: usize, channel: usize) -> f32 {
+        frame
+            .checked_sub(self.input_start)
+            .and_then(|frame| self.input.get(frame * self.channels + channel))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    fn mono(&self, frame: usize) -> f32 {
+        (0..self.channels)
+            .map(|channel| self.sample(frame, channel))
+            .sum()
+    }
+
+    /// Moves the grain at `nominal` to where it resembles the track after
+    /// the previous grain the most. Every few offsets are compared first,
+    /// then those around the best of them.
+    fn align(&mut self, nominal: usize, continuation: usize) -> usize {
+        let hop = self.hop();
+        let first = nominal.saturating_sub(self.tolerance).max(self.input_start);
+        let last = nominal + self.tolerance;
+
+        if first > last {
+            return nominal;
+        }
+
+        let mut reference = std::mem::take(&mut self.reference);
+        reference.clear();
+        reference.extend((0..hop).map(|i| self.mono(continuation + i)));
+
+        let mut search = std::mem::take(&mut self.search);
+        search.clear();
+        search.extend((first..last + hop).map(|frame| self.mono(frame)));
+
+        let similarity = |offset: usize, step: usize| {
+            let (correlation, energy) =
+                (0..hop)
+                    .step_by(step)
+                    .fold((0.0, 0.0), |(correlation, energy), i| {
+                        let sample = search[offset + i];
+
+                        (
+                            correlation + reference[i] * sample,
+                            energy + sample * sample,
+                        )
+                    });
+
+            correlation / (energy + f32::EPSILON).sqrt()
+        };
+        let best = |offsets: &mut dyn Iterator<Item = usize>, step: usize| {
+            offsets
+                .map(|offset| (offset, similarity(offset, step)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map_or(0, |(offset, _)| offset)
+        };
+
+        let range = last - first;
+        let coarse = best(&mut (0..=range).step_by(COARSE_STEP), COARSE_STEP);
+        let fine = best(
+            &mut (coarse.saturating_sub(COARSE_STEP)..=(coarse + COARSE_STEP).min(range)),
+            1,
+        );
+
+        self.reference = reference;
+        self.search = search;
+
+        first + fine
+    }
+
+    /// Adds the next grain to the output, returns `false` at the end of the
+    /// track.
+    fn synthesize(&mut self) -> bool {
+        let speed = self.speed.get().clamp(MIN_SPEED, MAX_SPEED) as f64;
+        let hop = self.hop();
+        let nominal = self.analysis.round() as usize;
+
+        self.fill(nominal.max(self.continuation.unwrap_or(0)) + self.tolerance + self.grain);
+
+        let start = match self.continuation {
+            // Nothing to stretch, so just continue the track.
+            Some(continuation) if speed == 1.0 => {
+                self.analysis = continuation as f64;
+                continuation
+            }
+            Some(continuation) => self.align(nominal, continuation),
+            None => nominal,
+        };
+
+        if self.is_exhausted && start >= self.input_end() {
+            return false;
+        }
+
+        let channels = self.channels;
+        self.output.copy_within(hop * channels.., 0);
+        self.output[hop * channels..].fill(0.0);
+
+        for i in 0..self.grain {
+            // The first grain has nothing to overlap with, so don't fade it in.
+            let weight = if self.continuation.is_none() && i < hop {
+                1.0
+            } else {
+                self.window[i]
+            };
+
+            for channel in 0..channels {
+                let sample = self.sample(start + i, channel);
+                self.output[i * channels + channel] += weight * sample;
+            }
+        }
+
+        self.continuation = Some(start + hop);
+        self.analysis += hop as f64 * speed;
+
+        let position = start as f64 / self.sample_rate as f64;
+        self.position
+            .store((position * 1000.0) as u64, Ordering::Relaxed);
+
+        // Drop what neither the next grain nor its alignment can reach anymore.
+        let keep = (self.analysis as usize)
+            .saturating_sub(self.tolerance)
+            .min(start + hop);
+        let drained = keep
+            .saturating_sub(self.input_start)
+            .min(self.input.len() / channels);
+        self.input.drain(..drained * channels);
+        self.input_start += drained;
+
+        true
+    }
+}
+
+impl<S> Iterator for Stretch<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.cursor == self.hop() * self.channels {
+            if !self.synthesize() {
+                return None;
+            }
+
+            self.cursor = 0;
+        }
+
+        let sample = self.output[self.cursor];
+        self.cursor += 1;
+
+        Some(sample)
+    }
+}
+
+impl<S> Source for Stretch<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let speed = self.speed.get().clamp(MIN_SPEED, MAX_SPEED);
+
+        self.inner
+            .total_duration()
+            .map(|duration| duration.div_f32(speed))
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+
+        let frame = (pos.as_secs_f64() * self.sample_rate as f64) as usize;
+
+        self.input.clear();
+        self.input_start = frame;
+        self.is_exhausted = false;
+        self.analysis = frame as f64;
+        self.continuation = None;
+        self.output.fill(0.0);
+        self.cursor = self.hop() * self.channels;
+        self.position
+            .store(pos.as_millis() as u64, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rodio::source::SineWave;
+
+    use super::*;
+
+    fn stretch(speed: f32) -> Stretch<impl Source<Item = f32>> {
+        let sine = SineWave::new(440.0).take_duration(Duration::from_secs(2));
+
+        Stretch::new(
+            sine,
+            Arc::new(Speed::new(speed)),
+            Arc::new(AtomicU64::new(0)),
+        )
+    }
+
+    fn crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .count()
+    }
+
+    #[test]
+    fn normal_speed_is_transparent() {
+        let sine = SineWave::new(440.0).take_duration(Duration::from_secs(2));
+
+        assert!(
+            sine.zip(stretch(1.0))
+                .take(48_000)
+                .all(|(a, b)| (a - b).abs() < 1e-5)
+        );
+    }
+
+    #[test]
+    fn double_speed_keeps_pitch() {
+        let stretched: Vec<f32> = stretch(2.0).collect();
+
+        // Two seconds play in one, give or take a grain.
+        let played = stretched.len() as f32 / 48_000.0;
+        assert!((played - 1.0).abs() < 0.05, "{played}");
+
+        // The sine still has 440 periods per second.
+        let second = &stretched[..48_000.min(stretched.len())];
+        let frequency = crossings(second) as f32 * 48_000.0 / second.len() as f32;
+        assert!((frequency - 440.0).abs() < 10.0, "{frequency}");
+    }
+
+    #[test]
+    fn reports_position_within_the_track() {
+        let position = Arc::new(AtomicU64::new(0));
+        let sine = SineWave::new(440.0).take_duration(Duration::from_secs(2));
+        let stretched = Stretch::new(sine, Arc::new(Speed::new(2.0)), position.clone());
+
+        // Half a second played is a second into the track.
+        stretched.take(24_000).for_each(drop);
+
+        let position = position.load(Ordering::Relaxed);
+        assert!(position.abs_diff(1000) < 50, "{position}");
+    }
+}
@@ -0,0 +1,238 @@
+use serde::Deserialize;
+
+use crate::client::MAX_PAGE_SIZE;
+use crate::thumbnail::Thumbnails;
+use crate::video::deserialize_count;
+use crate::{Client, Error, Playlist, Thumbnail, Video};
+
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub id: String,
+    pub title: String,
+    pub thumbnail: Option<Thumbnail>,
+    /// `None` if the channel hides its subscriber count.
+    pub subscriber_count: Option<u64>,
+    uploads: Option<String>,
+}
+
+impl Channel {
+    pub async fn fetch(client: Client, id: String) -> Result<Self, Error> {
+        Self::fetch_many(&client, std::slice::from_ref(&id))
+            .await?
+            .pop()
+            .ok_or(Error::NotFound(id))
+    }
+
+    /// Fetches the metadata of the given channels.
+    pub(crate) async fn fetch_many(client: &Client, ids: &[String]) -> Result<Vec<Self>, Error> {
+        let mut channels = Vec::with_capacity(ids.len());
+
+        for ids in ids.chunks(MAX_PAGE_SIZE) {
+            let resource: ChannelsResource = client
+                .get("channels")
+                .await?
+                .query(&[("part", "id,snippet,statistics,contentDetails")])
+                .query(&[("id", ids.join(","))])
+                .query(&[("maxResults", MAX_PAGE_SIZE)])
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            channels.extend(resource.items.into_iter().map(Channel::from));
+        }
+
+        Ok(channels)
+    }
+
+    /// Fetches the first page of the videos the channel uploaded, newest first.
+    pub async fn uploads(self, client: Client) -> Result<UploadsPage, Error> {
+        let Some(playlist_id) = self.uploads else {
+            return Ok(UploadsPage {
+                videos: Vec::new(),
+                next: None,
+            });
+        };
+
+        uploads_page(client, playlist_id, None).await
+    }
+
+    /// Fetches all public playlists of the channel, following the pagination.
+    pub async fn playlists(self, client: Client) -> Result<Vec<Playlist>, Error> {
+        Playlist::fetch_by_channel(&client, &self.id).await
+    }
+}
+
+/// One page of the uploads of a [`Channel`].
+#[derive(Debug, Clone)]
+pub struct UploadsPage {
+    pub videos: Vec<Video>,
+    pub next: Option<UploadsCursor>,
+}
+
+/// Points at the page following an [`UploadsPage`].
+#[derive(Debug, Clone)]
+pub struct UploadsCursor {
+    client: Client,
+    playlist_id: String,
+    page_token: String,
+}
+
+impl UploadsCursor {
+    pub async fn fetch(self) -> Result<UploadsPage, Error> {
+        uploads_page(self.client, self.playlist_id, Some(self.page_token)).await
+    }
+}
+
+async fn uploads_page(
+    client: Client,
+    playlist_id: String,
+    page_token: Option<String>,
+) -> Result<UploadsPage, Error> {
+    let (videos, next_page_token) =
+        Playlist::items_page(&client, &playlist_id, page_token.as_deref()).await?;
+
+    Ok(UploadsPage {
+        videos,
+        next: next_page_token.map(|page_token| UploadsCursor {
+            client,
+            playlist_id,
+            page_token,
+        }),
+    })
+}
+
+impl From<ChannelResource> for Channel {
+    fn from(resource: ChannelResource) -> Self {
+        let statistics = resource.statistics.unwrap_or_default();
+
+        Self {
+            id: resource.id,
+            title: resource.snippet.title,
+            thumbnail: resource.snippet.thumbnails.best(),
+            subscriber_count: if statistics.hidden_subscriber_count {
+                None
+            } else {
+                statistics.subscriber_count
+            },
+            uploads: resource
+                .content_details
+                .and_then(|details| details.related_playlists.uploads),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct ChannelsResource {
+    #[serde(default)]
+    items: Vec<ChannelResource>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ChannelResource {
+    id: String,
+    snippet: ChannelSnippet,
+    statistics: Option<ChannelStatistics>,
+    content_details: Option<ChannelContentDetails>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ChannelSnippet {
+    title: String,
+    #[serde(default)]
+    thumbnails: Thumbnails,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ChannelStatistics {
+    #[serde(default, deserialize_with = "deserialize_count")]
+    subscriber_count: Option<u64>,
+    #[serde(default)]
+    hidden_subscriber_count: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ChannelContentDetails {
+    related_playlists: RelatedPlaylists,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct RelatedPlaylists {
+    uploads: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_channels_resource() {
+        let json = r#"
+{
+  "kind": "youtube#channelListResponse",
+  "etag": "mA2gkT1FQf1WqkN0yQ4Gm6oYdBg",
+  "pageInfo": {
+    "totalResults": 1,
+    "resultsPerPage": 5
+  },
+  "items": [
+    {
+      "kind": "youtube#channel",
+      "etag": "r9xZ6sVZl3l1v8G1p7PjVxw3U2A",
+      "id": "UCzH2vVrSpjwHNM0U3jJM0lQ",
+      "snippet": {
+        "title": "Justin Johnson",
+        "description": "Roots music, slide guitar and cigar box guitars.",
+        "customUrl": "@justinjohnsonlive",
+        "publishedAt": "2006-08-15T19:33:41Z",
+        "thumbnails": {
+          "default": {
+            "url": "https://yt3.ggpht.com/ytc/default.jpg",
+            "width": 88,
+            "height": 88
+          },
+          "medium": {
+            "url": "https://yt3.ggpht.com/ytc/medium.jpg",
+            "width": 240,
+            "height": 240
+          },
+          "high": {
+            "url": "https://yt3.ggpht.com/ytc/high.jpg",
+            "width": 800,
+            "height": 800
+          }
+        },
+        "country": "US"
+      },
+      "contentDetails": {
+        "relatedPlaylists": {
+          "likes": "",
+          "uploads": "UUzH2vVrSpjwHNM0U3jJM0lQ"
+        }
+      },
+      "statistics": {
+        "viewCount": "213541873",
+        "subscriberCount": "1190000",
+        "hiddenSubscriberCount": false,
+        "videoCount": "1052"
+      }
+    }
+  ]
+}
+"#;
+
+        let result: ChannelsResource = serde_json::from_str(json).unwrap();
+        let channel = Channel::from(result.items.into_iter().next().unwrap());
+        assert_eq!(channel.title, "Justin Johnson");
+        assert_eq!(channel.subscriber_count, Some(1190000));
+        assert_eq!(
+            channel.thumbnail.map(|thumbnail| thumbnail.url).as_deref(),
+            Some("https://yt3.ggpht.com/ytc/high.jpg")
+        );
+        assert_eq!(channel.uploads.as_deref(), Some("UUzH2vVrSpjwHNM0U3jJM0lQ"));
+    }
+}
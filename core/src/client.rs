@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::oauth::{self, DeviceAuthorization, Token};
+use crate::{Config, Error};
+
+const API_URL: &str = "https://www.googleapis.com/youtube/v3";
+
+/// Upper bound of `maxResults` and of the ids per request of the Data API.
+pub(crate) const MAX_PAGE_SIZE: usize = 50;
+
+/// HTTP client shared by all YouTube Data API calls.
+///
+/// Requests are authorized with the OAuth token if the user logged in and
+/// fall back to the API key otherwise. Expired tokens are refreshed on demand.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    config: Config,
+    token: Arc<Mutex<Option<Token>>>,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        let token = match config.token_path().map(Token::load) {
+            Some(Ok(token)) => token,
+            Some(Err(err)) => {
+                tracing::warn!("ignoring the saved token: {err}");
+                None
+            }
+            None => None,
+        };
+
+        Self {
+            http: reqwest::Client::new(),
+            config,
+            token: Arc::new(Mutex::new(token)),
+        }
+    }
+
+    pub async fn is_logged_in(self) -> bool {
+        self.token.lock().await.is_some()
+    }
+
+    /// Starts the device flow, the returned code has to be entered by the
+    /// user before [`Client::login`] can finish.
+    pub async fn request_device_code(self) -> Result<DeviceAuthorization, Error> {
+        let credentials = self.config.oauth().ok_or(Error::MissingCredentials)?;
+
+        oauth::request_device_code(&self.http, credentials).await
+    }
+
+    pub async fn login(self, authorization: DeviceAuthorization) -> Result<(), Error> {
+        let credentials = self.config.oauth().ok_or(Error::MissingCredentials)?;
+        let token = oauth::poll_token(&self.http, credentials, &authorization).await?;
+
+        if let Some(path) = self.config.token_path() {
+            token.save(path)?;
+        }
+
+        *self.token.lock().await = Some(token);
+
+        Ok(())
+    }
+
+    pub async fn logout(self) -> Result<(), Error> {
+        *self.token.lock().await = None;
+
+        match self.config.token_path() {
+            Some(path) => Token::remove(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds an authorized `GET` request for `endpoint`, e.g. `"search"`.
+    pub(crate) async fn get(&self, endpoint: &str) -> Result<reqwest::RequestBuilder, Error> {
+        let request = self.http.get(format!("{API_URL}/{endpoint}"));
+
+        if let Some(access_token) = self.access_token().await? {
+            return Ok(request.bearer_auth(access_token));
+        }
+
+        match self.config.api_key() {
+            Some(api_key) => Ok(request.query(&[("key", api_key)])),
+            None => Err(Error::MissingCredentials),
+        }
+    }
+
+    async fn access_token(&self) -> Result<Option<String>, Error> {
+        // Hold the lock while refreshing, so concurrent requests share one refresh.
+        let mut token = self.token.lock().await;
+
+        let Some(current) = token.as_ref() else {
+            return Ok(None);
+        };
+
+        if !current.is_expired() {
+            return Ok(Some(current.access_token.clone()));
+        }
+
+        let credentials = self.config.oauth().ok_or(Error::MissingCredentials)?;
+        let refreshed = match oauth::refresh(&self.http, credentials, current).await {
+            Ok(refreshed) => refreshed,
+            Err(err @ Error::AuthorizationExpired) => {
+                *token = None;
+
+                // Otherwise the next start loads the revoked token again.
+                if let Some(Err(error)) = self.config.token_path().map(Token::remove) {
+                    tracing::warn!("removing the expired token failed: {error}");
+                }
+
+                return Err(err);
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let Some(path) = self.config.token_path() {
+            refreshed.save(path)?;
+        }
+
+        let access_token = refreshed.access_token.clone();
+        *token = Some(refreshed);
+
+        Ok(Some(access_token))
+    }
+}
@@ -0,0 +1,176 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::Error;
+use crate::oauth::Credentials;
+
+/// Environment variable the YouTube Data API key is read from.
+pub const API_KEY_VAR: &str = "YT_TOKEN";
+
+pub(crate) const APP_NAME: &str = "yt-music-player";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    api_key: Option<String>,
+    oauth: Option<Credentials>,
+    token_path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Resolves the configuration from the environment and the default config file.
+    pub fn load() -> Result<Self, Error> {
+        Self::builder().build()
+    }
+
+    pub fn api_key(&self) -> Option<&str> {
+        self.api_key.as_deref()
+    }
+
+    pub fn oauth(&self) -> Option<&Credentials> {
+        self.oauth.as_ref()
+    }
+
+    /// Where the OAuth token is persisted between runs.
+    pub fn token_path(&self) -> Option<&Path> {
+        self.token_path.as_deref()
+    }
+
+    /// `$XDG_CONFIG_HOME/yt-music-player/config.toml` or its platform equivalent.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_NAME).join("config.toml"))
+    }
+
+    /// `$XDG_DATA_HOME/yt-music-player/token.json` or its platform equivalent.
+    pub fn default_token_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_NAME).join("token.json"))
+    }
+}
+
+/// Resolves the API key from, in order: an explicit value, the `YT_TOKEN`
+/// environment variable and the `api_key` entry of the config file. OAuth
+/// credentials are taken from the builder or the `[oauth]` table.
+#[derive(Debug, Default)]
+pub struct Builder {
+    api_key: Option<String>,
+    oauth: Option<Credentials>,
+    path: Option<PathBuf>,
+    token_path: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn oauth(mut self, credentials: Credentials) -> Self {
+        self.oauth = Some(credentials);
+        self
+    }
+
+    /// Reads the config file from `path` instead of [`Config::default_path`].
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Persists the OAuth token at `path` instead of [`Config::default_token_path`].
+    pub fn token_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.token_path = Some(path.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Config, Error> {
+        let file = match self.path.or_else(Config::default_path) {
+            Some(path) => File::read(&path)?,
+            None => File::default(),
+        };
+
+        let api_key = [self.api_key, std::env::var(API_KEY_VAR).ok(), file.api_key]
+            .into_iter()
+            .flatten()
+            .find(|key| !key.is_empty());
+        let oauth = self.oauth.or(file.oauth);
+
+        if api_key.is_none() && oauth.is_none() {
+            return Err(Error::MissingCredentials);
+        }
+
+        Ok(Config {
+            api_key,
+            oauth,
+            token_path: self.token_path.or_else(Config::default_token_path),
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+struct File {
+    api_key: Option<String>,
+    oauth: Option<Credentials>,
+}
+
+impl File {
+    fn read(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_file() {
+        let file: File = toml::from_str(r#"api_key = "AIzaSy-test""#).unwrap();
+        assert_eq!(
+            file,
+            File {
+                api_key: Some("AIzaSy-test".to_string()),
+                oauth: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_oauth_section() {
+        let file: File = toml::from_str(
+            r#"
+            [oauth]
+            client_id = "1234.apps.googleusercontent.com"
+            client_secret = "GOCSPX-secret"
+            token_url = "http://127.0.0.1:8080/token"
+            "#,
+        )
+        .unwrap();
+
+        let oauth = file.oauth.unwrap();
+        assert_eq!(oauth.client_id, "1234.apps.googleusercontent.com");
+        assert_eq!(oauth.endpoints.token_url, "http://127.0.0.1:8080/token");
+        assert_eq!(
+            oauth.endpoints.device_code_url,
+            "https://oauth2.googleapis.com/device/code"
+        );
+    }
+
+    #[test]
+    fn explicit_api_key() {
+        let config = Config::builder()
+            .api_key("AIzaSy-explicit")
+            .path("/nonexistent/config.toml")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.api_key(), Some("AIzaSy-explicit"));
+    }
+}
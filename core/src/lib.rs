@@ -1,27 +1,76 @@
 pub mod backend;
+mod channel;
+mod client;
+mod config;
+pub mod oauth;
+mod playlist;
+mod preferences;
+mod search;
+mod thumbnail;
+pub mod track;
 mod video;
 
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod mock_server;
+
 pub use backend::Backend;
+pub use channel::{Channel, UploadsCursor, UploadsPage};
+pub use client::Client;
+pub use config::Config;
+pub use playlist::Playlist;
+pub use preferences::Preferences;
+pub use search::{
+    INVIDIOUS_URL, Invidious, PIPED_URL, Piped, ProviderKind, Results, SearchCursor, SearchItem,
+    SearchPage, SearchProvider, search,
+};
+pub use thumbnail::Thumbnail;
+pub use track::{LocalFile, Track};
 pub use video::Video;
 
+use std::io;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
     #[error("request failed: {0}")]
     RequestFailed(Arc<reqwest::Error>),
-    // #[error("io operation failed: {0}")]
-    // IOFailed(Arc<io::Error>),
+    #[error("io operation failed: {0}")]
+    IOFailed(Arc<io::Error>),
     // #[error("docker operation failed: {0}")]
     // DockerFailed(&'static str),
     // #[error("executor failed: {0}")]
     // ExecutorFailed(&'static str),
     #[error("deserialization failed: {0}")]
     SerdeFailed(Arc<serde_json::Error>),
-    // #[error("deserialization failed")]
-    // DecoderFailed(Arc<decoder::Error>),
-    // #[error("task join failed: {0}")]
-    // JoinFailed(Arc<task::JoinError>),
+    #[error("invalid config file: {0}")]
+    ConfigInvalid(Arc<toml::de::Error>),
+    #[error("no credentials found: set YT_TOKEN, `api_key` or `[oauth]` in the config file")]
+    MissingCredentials,
+    #[error("access was denied by the user")]
+    AccessDenied,
+    #[error("authorization expired, please log in again")]
+    AuthorizationExpired,
+    #[error("authorization failed: {0}")]
+    AuthorizationFailed(String),
+    #[error("resource not found: {0}")]
+    NotFound(String),
+    #[error("extracting the video failed: {0}")]
+    ExtractionFailed(Arc<youtube_dl::Error>),
+    #[error("the file size of video {0} is unknown")]
+    MissingFilesize(String),
+    #[error("setting up the stream failed: {0}")]
+    StreamFailed(String),
+    #[error("audio output failed: {0}")]
+    OutputFailed(String),
+    #[error("decoding failed: {0}")]
+    DecoderFailed(Arc<rodio::decoder::DecoderError>),
+    #[error("the player is no longer running")]
+    PlayerStopped,
+    #[error("seeking failed: {0}")]
+    SeekFailed(Arc<rodio::source::SeekError>),
+    #[error("task join failed: {0}")]
+    JoinFailed(Arc<tokio::task::JoinError>),
     // #[error("no suitable executor was found: neither llama-server nor docker are installed")]
     // NoExecutorAvailable,
 }
@@ -37,3 +86,39 @@ impl From<serde_json::Error> for Error {
         Self::SerdeFailed(Arc::new(error))
     }
 }
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::IOFailed(Arc::new(error))
+    }
+}
+
+impl From<youtube_dl::Error> for Error {
+    fn from(error: youtube_dl::Error) -> Self {
+        Self::ExtractionFailed(Arc::new(error))
+    }
+}
+
+impl From<rodio::source::SeekError> for Error {
+    fn from(error: rodio::source::SeekError) -> Self {
+        Self::SeekFailed(Arc::new(error))
+    }
+}
+
+impl From<rodio::decoder::DecoderError> for Error {
+    fn from(error: rodio::decoder::DecoderError) -> Self {
+        Self::DecoderFailed(Arc::new(error))
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::JoinFailed(Arc::new(error))
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Self::ConfigInvalid(Arc::new(error))
+    }
+}
@@ -0,0 +1,416 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+const SCOPE: &str = "https://www.googleapis.com/auth/youtube.readonly";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// OAuth client registered in the Google Cloud console as a "TV and Limited
+/// Input devices" application.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Credentials {
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(flatten)]
+    pub endpoints: Endpoints,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Endpoints {
+    pub device_code_url: String,
+    pub token_url: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            device_code_url: "https://oauth2.googleapis.com/device/code".to_string(),
+            token_url: "https://oauth2.googleapis.com/token".to_string(),
+        }
+    }
+}
+
+/// The code the user has to enter at `verification_url` to grant access.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct DeviceAuthorization {
+    pub user_code: String,
+    #[serde(alias = "verification_uri")]
+    pub verification_url: String,
+    device_code: String,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub access_token: String,
+    refresh_token: Option<String>,
+    expires_at: DateTime<Utc>,
+}
+
+impl Token {
+    fn from_grant(grant: Grant, refresh_token: Option<String>) -> Self {
+        Self {
+            access_token: grant.access_token,
+            // Refresh responses usually omit the refresh token, keep the old one then.
+            refresh_token: grant.refresh_token.or(refresh_token),
+            expires_at: Utc::now() + chrono::Duration::seconds(grant.expires_in),
+        }
+    }
+
+    /// Treats tokens as expired a minute early, so they don't run out mid-request.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at - chrono::Duration::seconds(60) <= Utc::now()
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        // The refresh token grants access to the account, so only the user may read it.
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(path)?;
+
+        // The mode only applies to new files, tighten ones written by older versions as well.
+        #[cfg(unix)]
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+
+        Ok(file.write_all(serde_json::to_string(self)?.as_bytes())?)
+    }
+
+    pub fn remove(path: &Path) -> Result<(), Error> {
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TokenResponse {
+    Granted(Grant),
+    Failed(ErrorResponse),
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Grant {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {description}", self.error),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+pub(crate) async fn request_device_code(
+    http: &reqwest::Client,
+    credentials: &Credentials,
+) -> Result<DeviceAuthorization, Error> {
+    Ok(http
+        .post(&credentials.endpoints.device_code_url)
+        .form(&[
+            ("client_id", credentials.client_id.as_str()),
+            ("scope", SCOPE),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Polls the token endpoint until the user granted or denied access.
+pub(crate) async fn poll_token(
+    http: &reqwest::Client,
+    credentials: &Credentials,
+    authorization: &DeviceAuthorization,
+) -> Result<Token, Error> {
+    let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+    let mut interval = Duration::from_secs(authorization.interval);
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        if Instant::now() >= deadline {
+            return Err(Error::AuthorizationExpired);
+        }
+
+        let response: TokenResponse = http
+            .post(&credentials.endpoints.token_url)
+            .form(&[
+                ("client_id", credentials.client_id.as_str()),
+                ("client_secret", credentials.client_secret.as_str()),
+                ("device_code", authorization.device_code.as_str()),
+                ("grant_type", DEVICE_CODE_GRANT),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match response {
+            TokenResponse::Granted(grant) => return Ok(Token::from_grant(grant, None)),
+            TokenResponse::Failed(response) => match response.error.as_str() {
+                "authorization_pending" => {}
+                "slow_down" => interval += Duration::from_secs(5),
+                "access_denied" => return Err(Error::AccessDenied),
+                "expired_token" => return Err(Error::AuthorizationExpired),
+                _ => return Err(Error::AuthorizationFailed(response.to_string())),
+            },
+        }
+    }
+}
+
+pub(crate) async fn refresh(
+    http: &reqwest::Client,
+    credentials: &Credentials,
+    token: &Token,
+) -> Result<Token, Error> {
+    let Some(refresh_token) = &token.refresh_token else {
+        return Err(Error::AuthorizationExpired);
+    };
+
+    let response: TokenResponse = http
+        .post(&credentials.endpoints.token_url)
+        .form(&[
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+            ("refresh_token", refresh_token.as_str()),
+            ("grant_type", "refresh_token"),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    match response {
+        TokenResponse::Granted(grant) => Ok(Token::from_grant(grant, Some(refresh_token.clone()))),
+        TokenResponse::Failed(response) if response.error == "invalid_grant" => {
+            Err(Error::AuthorizationExpired)
+        }
+        TokenResponse::Failed(response) => Err(Error::AuthorizationFailed(response.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    fn endpoints(server: &MockServer) -> Endpoints {
+        Endpoints {
+            device_code_url: format!("{}/device/code", server.url),
+            token_url: format!("{}/token", server.url),
+        }
+    }
+
+    fn credentials(endpoints: Endpoints) -> Credentials {
+        Credentials {
+            client_id: "1234.apps.googleusercontent.com".to_string(),
+            client_secret: "GOCSPX-secret".to_string(),
+            endpoints,
+        }
+    }
+
+    #[test]
+    fn parse_device_authorization() {
+        let json = r#"{
+            "device_code": "AH-1Ng2B7QGQ3v6LcQqfHv0fHr2c5Vk",
+            "user_code": "GQVQ-JKEC",
+            "verification_url": "https://www.google.com/device",
+            "expires_in": 1800,
+            "interval": 5
+        }"#;
+
+        let authorization: DeviceAuthorization = serde_json::from_str(json).unwrap();
+        assert_eq!(authorization.user_code, "GQVQ-JKEC");
+        assert_eq!(
+            authorization.verification_url,
+            "https://www.google.com/device"
+        );
+        assert_eq!(authorization.interval, 5);
+    }
+
+    #[test]
+    fn parse_token_response() {
+        let json = r#"{
+            "access_token": "ya29.a0AfH6SMC",
+            "expires_in": 3599,
+            "refresh_token": "1//0gdGq7",
+            "scope": "https://www.googleapis.com/auth/youtube.readonly",
+            "token_type": "Bearer"
+        }"#;
+
+        let TokenResponse::Granted(grant) = serde_json::from_str(json).unwrap() else {
+            panic!("expected a granted token");
+        };
+        let token = Token::from_grant(grant, None);
+        assert_eq!(token.access_token, "ya29.a0AfH6SMC");
+        assert_eq!(token.refresh_token.as_deref(), Some("1//0gdGq7"));
+        assert!(!token.is_expired());
+
+        let json = r#"{
+            "error": "authorization_pending",
+            "error_description": "Precondition Required"
+        }"#;
+
+        let TokenResponse::Failed(response) = serde_json::from_str(json).unwrap() else {
+            panic!("expected an error response");
+        };
+        assert_eq!(response.error, "authorization_pending");
+    }
+
+    #[test]
+    fn refresh_keeps_refresh_token() {
+        let grant = Grant {
+            access_token: "ya29.new".to_string(),
+            expires_in: 3599,
+            refresh_token: None,
+        };
+
+        let token = Token::from_grant(grant, Some("1//0gdGq7".to_string()));
+        assert_eq!(token.refresh_token.as_deref(), Some("1//0gdGq7"));
+    }
+
+    #[tokio::test]
+    async fn device_flow() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{
+                  "device_code": "AH-1Ng2B7QGQ3v6LcQqfHv0fHr2c5Vk",
+                  "user_code": "GQVQ-JKEC",
+                  "verification_url": "https://www.google.com/device",
+                  "expires_in": 1800,
+                  "interval": 0
+                }"#,
+            ),
+            (428, r#"{ "error": "authorization_pending" }"#),
+            (
+                200,
+                r#"{ "access_token": "ya29.a0AfH6SMC", "expires_in": 3599, "refresh_token": "1//0gdGq7" }"#,
+            ),
+        ]);
+        let credentials = credentials(endpoints(&server));
+        let http = reqwest::Client::new();
+
+        let authorization = request_device_code(&http, &credentials).await.unwrap();
+        assert_eq!(authorization.user_code, "GQVQ-JKEC");
+
+        let token = poll_token(&http, &credentials, &authorization)
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "ya29.a0AfH6SMC");
+        assert_eq!(token.refresh_token.as_deref(), Some("1//0gdGq7"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].target, "/device/code");
+        assert!(
+            requests[0]
+                .body
+                .contains("client_id=1234.apps.googleusercontent.com")
+        );
+        assert_eq!(requests[1], requests[2]);
+        assert!(
+            requests[2]
+                .body
+                .contains("device_code=AH-1Ng2B7QGQ3v6LcQqfHv0fHr2c5Vk")
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_token() {
+        let server = MockServer::start(vec![
+            (200, r#"{ "access_token": "ya29.new", "expires_in": 3599 }"#),
+            (
+                400,
+                r#"{ "error": "invalid_grant", "error_description": "Token has been expired or revoked." }"#,
+            ),
+        ]);
+        let credentials = credentials(endpoints(&server));
+        let http = reqwest::Client::new();
+
+        let token = Token {
+            access_token: "ya29.old".to_string(),
+            refresh_token: Some("1//0gdGq7".to_string()),
+            expires_at: Utc::now(),
+        };
+        assert!(token.is_expired());
+
+        let refreshed = refresh(&http, &credentials, &token).await.unwrap();
+        assert_eq!(refreshed.access_token, "ya29.new");
+        assert_eq!(refreshed.refresh_token, token.refresh_token);
+        assert!(!refreshed.is_expired());
+
+        let result = refresh(&http, &credentials, &refreshed).await;
+        assert!(matches!(result, Err(Error::AuthorizationExpired)));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].target, "/token");
+        assert!(requests[0].body.contains("grant_type=refresh_token"));
+        assert!(requests[0].body.contains("refresh_token=1%2F%2F0gdGq7"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_token_is_private() {
+        let path =
+            std::env::temp_dir().join(format!("player-core-token-{}.json", std::process::id()));
+        let token = Token {
+            access_token: "ya29.a0AfH6SMC".to_string(),
+            refresh_token: Some("1//0gdGq7".to_string()),
+            expires_at: Utc::now(),
+        };
+
+        std::fs::write(&path, "").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        token.save(&path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(Token::load(&path).unwrap(), Some(token));
+        Token::remove(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+
+use crate::client::MAX_PAGE_SIZE;
+use crate::{Client, Error, Video};
+
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    pub id: String,
+    pub title: String,
+    pub channel_title: String,
+    pub item_count: u64,
+}
+
+impl Playlist {
+    pub async fn fetch(client: Client, id: String) -> Result<Self, Error> {
+        Self::fetch_many(&client, std::slice::from_ref(&id))
+            .await?
+            .pop()
+            .ok_or(Error::NotFound(id))
+    }
+
+    /// Fetches the metadata of the given playlists.
+    pub(crate) async fn fetch_many(client: &Client, ids: &[String]) -> Result<Vec<Self>, Error> {
+        let mut playlists = Vec::with_capacity(ids.len());
+
+        for ids in ids.chunks(MAX_PAGE_SIZE) {
+            let resource: PlaylistsResource = client
+                .get("playlists")
+                .await?
+                .query(&[("part", "id,snippet,contentDetails")])
+                .query(&[("id", ids.join(","))])
+                .query(&[("maxResults", MAX_PAGE_SIZE)])
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            playlists.extend(resource.items.into_iter().map(Playlist::from));
+        }
+
+        Ok(playlists)
+    }
+
+    /// Fetches all public playlists of a channel, following the pagination.
+    pub(crate) async fn fetch_by_channel(
+        client: &Client,
+        channel_id: &str,
+    ) -> Result<Vec<Self>, Error> {
+        let mut playlists = Vec::new();
+        let mut page_token = None;
+
+        loop {
+            let mut request = client
+                .get("playlists")
+                .await?
+                .query(&[("part", "id,snippet,contentDetails")])
+                .query(&[("channelId", channel_id)])
+                .query(&[("maxResults", MAX_PAGE_SIZE)]);
+
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+
+            let resource: PlaylistsResource = request.send().await?.json().await?;
+
+            playlists.extend(resource.items.into_iter().map(Playlist::from));
+
+            page_token = resource.next_page_token;
+
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    /// Fetches all videos of the playlist, following the `playlistItems` pagination.
+    ///
+    /// Private and deleted videos are skipped.
+    pub async fn items(self, client: Client) -> Result<Vec<Video>, Error> {
+        let mut ids = Vec::with_capacity(self.item_count as usize);
+        let mut page_token = None;
+
+        loop {
+            let (page, next_page_token) =
+                fetch_item_ids(&client, &self.id, page_token.as_deref()).await?;
+
+            ids.extend(page);
+            page_token = next_page_token;
+
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Video::fetch_many(&client, &ids).await
+    }
+
+    /// Fetches the videos on the page `page_token` points at, the first one
+    /// if `None`, along with the token of the next page.
+    pub(crate) async fn items_page(
+        client: &Client,
+        id: &str,
+        page_token: Option<&str>,
+    ) -> Result<(Vec<Video>, Option<String>), Error> {
+        let (ids, next_page_token) = fetch_item_ids(client, id, page_token).await?;
+
+        Ok((Video::fetch_many(client, &ids).await?, next_page_token))
+    }
+}
+
+async fn fetch_item_ids(
+    client: &Client,
+    playlist_id: &str,
+    page_token: Option<&str>,
+) -> Result<(Vec<String>, Option<String>), Error> {
+    let mut request = client
+        .get("playlistItems")
+        .await?
+        .query(&[("part", "contentDetails")])
+        .query(&[("playlistId", playlist_id)])
+        .query(&[("maxResults", MAX_PAGE_SIZE)]);
+
+    if let Some(page_token) = page_token {
+        request = request.query(&[("pageToken", page_token)]);
+    }
+
+    let page: PlaylistItemsResource = request.send().await?.json().await?;
+
+    let ids = page
+        .items
+        .into_iter()
+        .map(|item| item.content_details.video_id)
+        .collect();
+
+    Ok((ids, page.next_page_token))
+}
+
+impl From<PlaylistResource> for Playlist {
+    fn from(resource: PlaylistResource) -> Self {
+        Self {
+            id: resource.id,
+            title: resource.snippet.title,
+            channel_title: resource.snippet.channel_title,
+            item_count: resource.content_details.item_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistsResource {
+    next_page_token: Option<String>,
+    items: Vec<PlaylistResource>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistResource {
+    id: String,
+    snippet: PlaylistSnippet,
+    content_details: PlaylistContentDetails,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSnippet {
+    title: String,
+    channel_title: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistContentDetails {
+    item_count: u64,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemsResource {
+    next_page_token: Option<String>,
+    items: Vec<PlaylistItem>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItem {
+    content_details: PlaylistItemContentDetails,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemContentDetails {
+    video_id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_playlists_resource() {
+        let json = r#"
+{
+  "kind": "youtube#playlistListResponse",
+  "etag": "Vd9Bq7NhKj1lqHhR2ZpOqkVbZ5E",
+  "pageInfo": {
+    "totalResults": 1,
+    "resultsPerPage": 5
+  },
+  "items": [
+    {
+      "kind": "youtube#playlist",
+      "etag": "3Jd3xvD3r8mHk6YQZ9cK8b3tK2s",
+      "id": "OLAK5uy_kxVnMzD3yWq2rjKtl4mTjZy9kJ3mPqH0A",
+      "snippet": {
+        "publishedAt": "2019-10-31T03:40:45Z",
+        "channelId": "UC1-evqmLMusdbdD65Sz0-5Q",
+        "title": "Album - At Fillmore East",
+        "description": "",
+        "channelTitle": "The Allman Brothers Band - Topic",
+        "localized": {
+          "title": "Album - At Fillmore East",
+          "description": ""
+        }
+      },
+      "contentDetails": {
+        "itemCount": 7
+      }
+    }
+  ]
+}
+"#;
+
+        let result: PlaylistsResource = serde_json::from_str(json).unwrap();
+        let playlist = Playlist::from(result.items.into_iter().next().unwrap());
+        assert_eq!(playlist.id, "OLAK5uy_kxVnMzD3yWq2rjKtl4mTjZy9kJ3mPqH0A");
+        assert_eq!(playlist.title, "Album - At Fillmore East");
+        assert_eq!(playlist.channel_title, "The Allman Brothers Band - Topic");
+        assert_eq!(playlist.item_count, 7);
+    }
+
+    #[test]
+    fn parse_playlist_items_resource() {
+        let json = r#"
+{
+  "kind": "youtube#playlistItemListResponse",
+  "etag": "qN8wLZ2zJY1jG7nN4nKzH6dV1Mw",
+  "nextPageToken": "EAAaBlBUOkNBVQ",
+  "items": [
+    {
+      "kind": "youtube#playlistItem",
+      "etag": "k7cHn3lB1s5Q3Qx0yS2aS1mP4dE",
+      "id": "T0xBSzV1eV9reFZuTXpEM3lXcTJyakt0bDRtVGpaeTlrSjNtUHFIMEEuNTZCNDRGNkQxMDU1N0NDNg",
+      "contentDetails": {
+        "videoId": "FUvxRjYqjEQ",
+        "videoPublishedAt": "2014-09-11T17:03:26Z"
+      }
+    }
+  ],
+  "pageInfo": {
+    "totalResults": 7,
+    "resultsPerPage": 1
+  }
+}
+"#;
+
+        let result: PlaylistItemsResource = serde_json::from_str(json).unwrap();
+        assert_eq!(result.next_page_token.as_deref(), Some("EAAaBlBUOkNBVQ"));
+        assert_eq!(result.items[0].content_details.video_id, "FUvxRjYqjEQ");
+    }
+}
@@ -0,0 +1,124 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{CACHE_SIZE, Gains, Preset, Quality, TARGET_LOUDNESS};
+use crate::config::APP_NAME;
+use crate::{Error, INVIDIOUS_URL, PIPED_URL, ProviderKind};
+
+/// Settings changed from within the app, kept across restarts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Linear gain of the output, `1.0` being unity gain.
+    pub volume: f32,
+    pub muted: bool,
+    /// The name of the output device, `None` for the default device.
+    pub output_device: Option<String>,
+    /// How long consecutive tracks fade into each other, `0.0` to play them
+    /// back to back.
+    pub crossfade_secs: f32,
+    pub normalize: bool,
+    /// The loudness to normalize tracks to, in LUFS.
+    pub target_loudness: f32,
+    /// Whether the equalizer is applied.
+    pub equalizer: bool,
+    /// The gain of each equalizer band in dB.
+    pub equalizer_gains: Gains,
+    /// The equalizer presets saved by the user.
+    pub presets: Vec<Preset>,
+    /// The playback speed, `1.0` being normal speed.
+    pub speed: f32,
+    /// The folder scanned for local audio files.
+    pub library_folder: Option<PathBuf>,
+    /// How much played audio is cached, in bytes.
+    pub cache_size: u64,
+    /// The audio stream preferred when playing videos.
+    pub quality: Quality,
+    /// Whether to limit the bitrate, e.g. on metered connections.
+    pub data_saver: bool,
+    /// Where videos are searched.
+    pub search_provider: ProviderKind,
+    /// The Invidious instance searched with [`ProviderKind::Invidious`].
+    pub invidious_url: String,
+    /// The Piped API instance searched with [`ProviderKind::Piped`].
+    pub piped_url: String,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            output_device: None,
+            crossfade_secs: 0.0,
+            normalize: false,
+            target_loudness: TARGET_LOUDNESS,
+            equalizer: false,
+            equalizer_gains: [0.0; 10],
+            presets: Vec::new(),
+            speed: 1.0,
+            library_folder: None,
+            cache_size: CACHE_SIZE,
+            quality: Quality::default(),
+            data_saver: false,
+            search_provider: ProviderKind::default(),
+            invidious_url: INVIDIOUS_URL.to_string(),
+            piped_url: PIPED_URL.to_string(),
+        }
+    }
+}
+
+impl Preferences {
+    /// Loads the preferences from [`Preferences::default_path`], falling back
+    /// to the defaults if there are none yet.
+    pub fn load() -> Result<Self, Error> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = Self::default_path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+
+    /// `$XDG_DATA_HOME/yt-music-player/preferences.json` or its platform equivalent.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_NAME).join("preferences.json"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_partial_preferences() {
+        let preferences: Preferences = serde_json::from_str(r#"{ "volume": 0.5 }"#).unwrap();
+        assert_eq!(
+            preferences,
+            Preferences {
+                volume: 0.5,
+                ..Preferences::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,336 @@
+mod invidious;
+mod piped;
+
+pub use invidious::{INVIDIOUS_URL, Invidious};
+pub use piped::{PIPED_URL, Piped};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::video::Snippet;
+use crate::{Channel, Client, Error, Playlist, Video};
+
+const PAGE_SIZE: &str = "25";
+
+/// A video, playlist or channel found by [`search`].
+#[derive(Debug, Clone)]
+pub enum SearchItem {
+    Video(Video),
+    Playlist(Playlist),
+    Channel(Channel),
+}
+
+/// One page of search results.
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub items: Vec<SearchItem>,
+    /// `None` if the provider doesn't tell.
+    pub total_results: Option<u64>,
+    pub next: Option<SearchCursor>,
+}
+
+/// Points at the page following a [`SearchPage`].
+#[derive(Debug, Clone)]
+pub struct SearchCursor {
+    provider: Arc<dyn SearchProvider>,
+    query: String,
+    page_token: String,
+}
+
+impl SearchCursor {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub async fn fetch(self) -> Result<SearchPage, Error> {
+        search_page(self.provider, self.query, Some(self.page_token)).await
+    }
+}
+
+/// Finds videos, and depending on the provider playlists and channels.
+pub trait SearchProvider: fmt::Debug + Send + Sync {
+    /// Fetches the results for `query` on the page `page_token` points at,
+    /// the first one if `None`.
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        page_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Results, Error>>;
+}
+
+/// One page of results of a [`SearchProvider`].
+#[derive(Debug, Clone)]
+pub struct Results {
+    pub items: Vec<SearchItem>,
+    pub total_results: Option<u64>,
+    /// Points at the next page, `None` on the last one.
+    pub next_page_token: Option<String>,
+}
+
+/// The search providers to choose from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// The YouTube Data API, which needs an API key or a login.
+    #[default]
+    YouTube,
+    Invidious,
+    Piped,
+}
+
+impl ProviderKind {
+    pub const ALL: [Self; 3] = [Self::YouTube, Self::Invidious, Self::Piped];
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::YouTube => "YouTube Data API",
+            Self::Invidious => "Invidious",
+            Self::Piped => "Piped",
+        })
+    }
+}
+
+pub async fn search(provider: Arc<dyn SearchProvider>, query: String) -> Result<SearchPage, Error> {
+    search_page(provider, query, None).await
+}
+
+async fn search_page(
+    provider: Arc<dyn SearchProvider>,
+    query: String,
+    page_token: Option<String>,
+) -> Result<SearchPage, Error> {
+    let results = provider.search(&query, page_token.as_deref()).await?;
+
+    Ok(SearchPage {
+        items: results.items,
+        total_results: results.total_results,
+        next: results.next_page_token.map(|page_token| SearchCursor {
+            provider,
+            query,
+            page_token,
+        }),
+    })
+}
+
+impl SearchProvider for Client {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        page_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Results, Error>> {
+        search_data_api(self, query, page_token).boxed()
+    }
+}
+
+async fn search_data_api(
+    client: &Client,
+    query: &str,
+    page_token: Option<&str>,
+) -> Result<Results, Error> {
+    let mut request = client
+        .get("search")
+        .await?
+        .query(&[("part", "id,snippet")])
+        .query(&[("q", query)])
+        .query(&[("maxResults", PAGE_SIZE)])
+        .query(&[("type", "video,playlist,channel")]);
+
+    if let Some(page_token) = page_token {
+        request = request.query(&[("pageToken", page_token)]);
+    }
+
+    let list: ListResponse = request.send().await?.json().await?;
+
+    let ids: Vec<_> = list.items.into_iter().map(|search| search.id).collect();
+    let video_ids: Vec<_> = ids
+        .iter()
+        .filter_map(|id| match id {
+            Id::Video { video_id } => Some(video_id.clone()),
+            _ => None,
+        })
+        .collect();
+    let playlist_ids: Vec<_> = ids
+        .iter()
+        .filter_map(|id| match id {
+            Id::Playlist { playlist_id } => Some(playlist_id.clone()),
+            _ => None,
+        })
+        .collect();
+    let channel_ids: Vec<_> = ids
+        .iter()
+        .filter_map(|id| match id {
+            Id::Channel { channel_id } => Some(channel_id.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let (videos, playlists, channels) = futures::try_join!(
+        Video::fetch_many(client, &video_ids),
+        Playlist::fetch_many(client, &playlist_ids),
+        Channel::fetch_many(client, &channel_ids)
+    )?;
+
+    let mut videos: HashMap<_, _> = videos
+        .into_iter()
+        .map(|video| (video.id.clone(), video))
+        .collect();
+    let mut playlists: HashMap<_, _> = playlists
+        .into_iter()
+        .map(|playlist| (playlist.id.clone(), playlist))
+        .collect();
+    let mut channels: HashMap<_, _> = channels
+        .into_iter()
+        .map(|channel| (channel.id.clone(), channel))
+        .collect();
+
+    // Keep the relevance order of the search, the detail calls are grouped by kind.
+    let items = ids
+        .into_iter()
+        .filter_map(|id| match id {
+            Id::Video { video_id } => videos.remove(&video_id).map(SearchItem::Video),
+            Id::Playlist { playlist_id } => {
+                playlists.remove(&playlist_id).map(SearchItem::Playlist)
+            }
+            Id::Channel { channel_id } => channels.remove(&channel_id).map(SearchItem::Channel),
+        })
+        .collect();
+
+    Ok(Results {
+        items,
+        total_results: Some(list.page_info.total_results),
+        next_page_token: list.next_page_token,
+    })
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all_fields = "camelCase")]
+enum Id {
+    #[serde(rename = "youtube#video")]
+    Video { video_id: String },
+    #[serde(rename = "youtube#channel")]
+    Channel { channel_id: String },
+    #[serde(rename = "youtube#playlist")]
+    Playlist { playlist_id: String },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListResponse {
+    next_page_token: Option<String>,
+    page_info: PageInfo,
+    items: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    total_results: u64,
+    results_per_page: u64,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct SearchResult {
+    id: Id,
+    snippet: Snippet,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_id() {
+        let json = r#"
+      {
+        "kind": "youtube#video",
+        "videoId": "1pW_j_eJIlo"
+      }"#;
+
+        let id: Id = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            id,
+            Id::Video {
+                video_id: "1pW_j_eJIlo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_search_result() {
+        let json = r#"{
+      "kind": "youtube#searchListResponse",
+      "etag": "zKTHRvvYczZrAz9JayAYpOlPSws",
+      "nextPageToken": "CBkQAA",
+      "regionCode": "DE",
+      "pageInfo": {
+        "totalResults": 1000000,
+        "resultsPerPage": 25
+      },
+      "items": [
+        {
+          "kind": "youtube#searchResult",
+          "etag": "yJ-DyL6UtoYLkLxUZl1NriJnWYk",
+          "id": {
+            "kind": "youtube#video",
+            "videoId": "1pW_j_eJIlo"
+          },
+          "snippet": {
+            "publishedAt": "2025-04-16T19:12:18Z",
+            "channelId": "UCzH2vVrSpjwHNM0U3jJM0lQ",
+            "title": "Dark Blues Slide Guitar • &quot;Black Moon&quot;",
+            "description": "Unlock your guitar potential with exclusive lessons and tips at http://JustinJohnsonGuitar.com — from easy-to-follow basics to ...",
+            "thumbnails": {
+              "default": {
+                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/default.jpg",
+                "width": 120,
+                "height": 90
+              },
+              "medium": {
+                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/mqdefault.jpg",
+                "width": 320,
+                "height": 180
+              },
+              "high": {
+                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/hqdefault.jpg",
+                "width": 480,
+                "height": 360
+              }
+            },
+            "channelTitle": "Justin Johnson",
+            "liveBroadcastContent": "none",
+            "publishTime": "2025-04-16T19:12:18Z"
+          }
+        }
+      ]
+    }"#;
+
+        let result: ListResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(result.next_page_token.as_deref(), Some("CBkQAA"));
+        assert_eq!(
+            result.page_info,
+            PageInfo {
+                total_results: 1000000,
+                results_per_page: 25
+            }
+        );
+        assert_eq!(
+            result.items[0],
+            SearchResult {
+                id: Id::Video {
+                    video_id: "1pW_j_eJIlo".to_string()
+                },
+                snippet: Snippet {
+                    title: "Dark Blues Slide Guitar • &quot;Black Moon&quot;".to_string(),
+                    description: "Unlock your guitar potential with exclusive lessons and tips at http://JustinJohnsonGuitar.com — from easy-to-follow basics to ...".to_string()
+                }
+            }
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use super::{Results, SearchItem, SearchProvider};
+use crate::{Error, Thumbnail, Video};
+
+/// The instance searched unless configured otherwise.
+pub const INVIDIOUS_URL: &str = "https://inv.nadeko.net";
+
+/// Searches videos through the API of an Invidious instance, no API key
+/// needed.
+#[derive(Debug, Clone)]
+pub struct Invidious {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Invidious {
+    /// Searches the instance at `base_url`, e.g. [`INVIDIOUS_URL`].
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl SearchProvider for Invidious {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        page_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Results, Error>> {
+        async move {
+            // Pages are numbered, starting at 1.
+            let page: u32 = page_token.and_then(|page| page.parse().ok()).unwrap_or(1);
+
+            let items: Vec<Item> = self
+                .http
+                .get(format!("{}/api/v1/search", self.base_url))
+                .query(&[("q", query), ("type", "video")])
+                .query(&[("page", page)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let next_page_token = (!items.is_empty()).then(|| (page + 1).to_string());
+
+            Ok(Results {
+                items: items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        Item::Video {
+                            video_id,
+                            title,
+                            length_seconds,
+                            author,
+                            published,
+                            view_count,
+                            video_thumbnails,
+                        } => Some(SearchItem::Video(Video {
+                            id: video_id,
+                            title,
+                            duration: chrono::Duration::seconds(length_seconds),
+                            channel_title: author,
+                            published_at: published
+                                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0)),
+                            // Ordered from the largest to the smallest.
+                            thumbnail: video_thumbnails.into_iter().next(),
+                            tags: Vec::new(),
+                            view_count,
+                            like_count: None,
+                        })),
+                        Item::Other => None,
+                    })
+                    .collect(),
+                total_results: None,
+                next_page_token,
+            })
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Item {
+    Video {
+        video_id: String,
+        title: String,
+        length_seconds: i64,
+        #[serde(default)]
+        author: String,
+        /// Seconds since the epoch.
+        published: Option<i64>,
+        view_count: Option<u64>,
+        #[serde(default)]
+        video_thumbnails: Vec<Thumbnail>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_items() {
+        let json = r#"[
+          {
+            "type": "video",
+            "title": "Dark Blues Slide Guitar",
+            "videoId": "1pW_j_eJIlo",
+            "author": "Justin Johnson",
+            "authorId": "UCzH2vVrSpjwHNM0U3jJM0lQ",
+            "lengthSeconds": 263,
+            "viewCount": 12345,
+            "published": 1744830738,
+            "videoThumbnails": [
+              {
+                "quality": "maxres",
+                "url": "https://inv.nadeko.net/vi/1pW_j_eJIlo/maxres.jpg",
+                "width": 1280,
+                "height": 720
+              }
+            ]
+          },
+          {
+            "type": "channel",
+            "author": "Justin Johnson",
+            "authorId": "UCzH2vVrSpjwHNM0U3jJM0lQ",
+            "subCount": 1000
+          }
+        ]"#;
+
+        let items: Vec<Item> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            items,
+            [
+                Item::Video {
+                    video_id: "1pW_j_eJIlo".to_string(),
+                    title: "Dark Blues Slide Guitar".to_string(),
+                    length_seconds: 263,
+                    author: "Justin Johnson".to_string(),
+                    published: Some(1744830738),
+                    view_count: Some(12345),
+                    video_thumbnails: vec![Thumbnail {
+                        url: "https://inv.nadeko.net/vi/1pW_j_eJIlo/maxres.jpg".to_string(),
+                        width: Some(1280),
+                        height: Some(720),
+                    }],
+                },
+                Item::Other
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use super::{Results, SearchItem, SearchProvider};
+use crate::{Error, Thumbnail, Video};
+
+/// The API instance searched unless configured otherwise.
+pub const PIPED_URL: &str = "https://pipedapi.kavin.rocks";
+
+/// Searches videos through the API of a Piped instance, no API key needed.
+#[derive(Debug, Clone)]
+pub struct Piped {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Piped {
+    /// Searches the API instance at `base_url`, e.g. [`PIPED_URL`].
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl SearchProvider for Piped {
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        page_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Results, Error>> {
+        async move {
+            let request = match page_token {
+                Some(page_token) => self
+                    .http
+                    .get(format!("{}/nextpage/search", self.base_url))
+                    .query(&[("nextpage", page_token)]),
+                None => self.http.get(format!("{}/search", self.base_url)),
+            };
+
+            let response: Response = request
+                .query(&[("q", query), ("filter", "videos")])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            Ok(Results {
+                items: response
+                    .items
+                    .into_iter()
+                    .filter_map(Item::into_video)
+                    .map(SearchItem::Video)
+                    .collect(),
+                total_results: None,
+                next_page_token: response.nextpage,
+            })
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Response {
+    items: Vec<Item>,
+    nextpage: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Item {
+    Stream {
+        /// E.g. `/watch?v=1pW_j_eJIlo`.
+        url: String,
+        title: String,
+        /// In seconds, `-1` for live streams.
+        duration: i64,
+        #[serde(default)]
+        uploader_name: String,
+        /// Milliseconds since the epoch, `-1` if unknown.
+        uploaded: Option<i64>,
+        views: Option<u64>,
+        thumbnail: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl Item {
+    fn into_video(self) -> Option<Video> {
+        let Self::Stream {
+            url,
+            title,
+            duration,
+            uploader_name,
+            uploaded,
+            views,
+            thumbnail,
+        } = self
+        else {
+            return None;
+        };
+
+        Some(Video {
+            id: url.strip_prefix("/watch?v=")?.to_string(),
+            title,
+            duration: chrono::Duration::seconds(duration.max(0)),
+            channel_title: uploader_name,
+            published_at: uploaded
+                .filter(|millis| *millis >= 0)
+                .and_then(chrono::DateTime::from_timestamp_millis),
+            thumbnail: thumbnail.map(|url| Thumbnail {
+                url,
+                width: None,
+                height: None,
+            }),
+            tags: Vec::new(),
+            view_count: views,
+            like_count: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_response() {
+        let json = r#"{
+          "items": [
+            {
+              "url": "/watch?v=1pW_j_eJIlo",
+              "type": "stream",
+              "title": "Dark Blues Slide Guitar",
+              "uploaderName": "Justin Johnson",
+              "duration": 263,
+              "views": 12345,
+              "uploaded": 1744830738000,
+              "thumbnail": "https://pipedproxy.kavin.rocks/vi/1pW_j_eJIlo/hqdefault.jpg"
+            },
+            {
+              "url": "/channel/UCzH2vVrSpjwHNM0U3jJM0lQ",
+              "type": "channel",
+              "name": "Justin Johnson"
+            }
+          ],
+          "nextpage": "{\"url\":\"https://www.youtube.com/youtubei/v1/search\"}",
+          "suggestion": null,
+          "corrected": false
+        }"#;
+
+        let response: Response = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            response.nextpage.as_deref(),
+            Some(r#"{"url":"https://www.youtube.com/youtubei/v1/search"}"#)
+        );
+
+        let videos: Vec<_> = response
+            .items
+            .into_iter()
+            .filter_map(Item::into_video)
+            .map(|video| {
+                (
+                    video.id,
+                    video.duration.num_seconds(),
+                    video.channel_title,
+                    video.published_at.map(|date| date.timestamp()),
+                    video.view_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            videos,
+            [(
+                "1pW_j_eJIlo".to_string(),
+                263,
+                "Justin Johnson".to_string(),
+                Some(1744830738),
+                Some(12345)
+            )]
+        );
+    }
+}
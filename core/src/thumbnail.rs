@@ -0,0 +1,43 @@
+use serde::Deserialize;
+
+use crate::Error;
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl Thumbnail {
+    /// Downloads the encoded image, e.g. to hand it to an image widget.
+    pub async fn download(self) -> Result<Vec<u8>, Error> {
+        let bytes = reqwest::get(&self.url)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(bytes.to_vec())
+    }
+}
+
+/// The `thumbnails` object of a snippet, keyed by resolution.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub(crate) struct Thumbnails {
+    default: Option<Thumbnail>,
+    medium: Option<Thumbnail>,
+    high: Option<Thumbnail>,
+    standard: Option<Thumbnail>,
+    maxres: Option<Thumbnail>,
+}
+
+impl Thumbnails {
+    pub fn best(self) -> Option<Thumbnail> {
+        self.maxres
+            .or(self.standard)
+            .or(self.high)
+            .or(self.medium)
+            .or(self.default)
+    }
+}
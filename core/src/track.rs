@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{Error, Video};
+
+/// The file extensions of the audio files that can be played.
+pub const EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "m4a", "wav"];
+
+/// Something the backend can play.
+#[derive(Debug, Clone)]
+pub enum Track {
+    Video(Video),
+    File(LocalFile),
+}
+
+impl Track {
+    /// Identifies the track, the video id or the path of the file.
+    pub fn id(&self) -> Cow<'_, str> {
+        match self {
+            Self::Video(video) => Cow::Borrowed(&video.id),
+            Self::File(file) => file.path.to_string_lossy(),
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Self::Video(video) => &video.title,
+            Self::File(file) => &file.title,
+        }
+    }
+
+    /// The duration if known before decoding the track.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Video(video) => video.duration.to_std().ok(),
+            Self::File(_) => None,
+        }
+    }
+}
+
+impl From<Video> for Track {
+    fn from(video: Video) -> Self {
+        Self::Video(video)
+    }
+}
+
+impl From<LocalFile> for Track {
+    fn from(file: LocalFile) -> Self {
+        Self::File(file)
+    }
+}
+
+/// An audio file on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalFile {
+    pub path: PathBuf,
+    /// The file name without its extension.
+    pub title: String,
+}
+
+impl LocalFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let title = path
+            .file_stem()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+
+        Self { path, title }
+    }
+
+    /// Whether the file has one of the [`EXTENSIONS`].
+    pub fn is_supported(path: &Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                EXTENSIONS
+                    .iter()
+                    .any(|supported| supported.eq_ignore_ascii_case(extension))
+            })
+    }
+
+    /// Lists the audio files in `folder` and its subfolders, sorted by path.
+    ///
+    /// Only fails if `folder` itself can't be read, unreadable subfolders and
+    /// entries are skipped.
+    pub async fn scan(folder: PathBuf) -> Result<Vec<Self>, Error> {
+        Ok(tokio::task::spawn_blocking(move || scan(&folder)).await??)
+    }
+}
+
+fn scan(folder: &Path) -> io::Result<Vec<LocalFile>> {
+    let mut files = Vec::new();
+    let mut folders = vec![folder.to_path_buf()];
+
+    while let Some(current) = folders.pop() {
+        let entries = match std::fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(err) if current == folder => return Err(err),
+            Err(err) => {
+                tracing::debug!("skipping {}: {err}", current.display());
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                folders.push(path);
+            } else if LocalFile::is_supported(&path) {
+                files.push(LocalFile::new(path));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scan_folder() {
+        let folder = std::env::temp_dir().join(format!("player-scan-{}", std::process::id()));
+        std::fs::create_dir_all(folder.join("album")).unwrap();
+
+        for name in ["b.mp3", "notes.txt", "album/a.FLAC", "album/cover.jpg"] {
+            std::fs::write(folder.join(name), []).unwrap();
+        }
+
+        let files = scan(&folder);
+        std::fs::remove_dir_all(&folder).unwrap();
+
+        let titles: Vec<_> = files.unwrap().into_iter().map(|file| file.title).collect();
+        assert_eq!(titles, ["a", "b"]);
+    }
+
+    #[test]
+    fn scan_missing_folder() {
+        let folder = std::env::temp_dir().join(format!("player-missing-{}", std::process::id()));
+
+        let error = scan(&folder).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
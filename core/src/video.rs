@@ -1,97 +1,72 @@
-use chrono::{Duration, TimeDelta};
+use chrono::{DateTime, Duration, TimeDelta, Utc};
 use serde::{Deserialize, Deserializer};
 
-use crate::Error;
+use crate::client::MAX_PAGE_SIZE;
+use crate::thumbnail::Thumbnails;
+use crate::{Client, Error, Thumbnail};
 
 #[derive(Debug, Clone)]
 pub struct Video {
     pub id: String,
     pub title: String,
     pub duration: Duration,
-}
-
-#[derive(Debug, Deserialize, PartialEq, Eq)]
-#[serde(tag = "kind", rename_all_fields = "camelCase")]
-enum Id {
-    #[serde(rename = "youtube#video")]
-    Video { video_id: String },
-    #[serde(rename = "youtube#channel")]
-    Channel { channel_id: String },
-    #[serde(rename = "youtube#playlist")]
-    Playlist { playlist_id: String },
+    /// The channel that uploaded the video, empty if unknown.
+    pub channel_title: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub thumbnail: Option<Thumbnail>,
+    pub tags: Vec<String>,
+    /// `None` if unknown or hidden.
+    pub view_count: Option<u64>,
+    /// `None` if unknown or hidden.
+    pub like_count: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
-struct Snippet {
-    title: String,
-    description: String,
+pub(crate) struct Snippet {
+    pub title: String,
+    pub description: String,
 }
 
 impl Video {
-    pub async fn search(query: String) -> Result<Vec<Self>, Error> {
-        // FIXME: load token on run time
-        let token = env!("YT_TOKEN");
-
-        let response = reqwest::Client::new()
-            .get("https://www.googleapis.com/youtube/v3/search")
-            // FIXME add oAuth
-            // .header("Authorization", format!("Bearer {token}"))
-            .query(&[("part", "id,snippet")])
-            .query(&[("q", query)])
-            .query(&[("maxResults", "25")])
-            .query(&[("key", token)])
-            .query(&[("type", "video")])
-            .send()
-            .await?;
-
-        let list: ListResponse = response.json().await?;
-
-        let ids: Vec<_> = list
-            .items
-            .into_iter()
-            .filter_map(|search| match search.id {
-                Id::Video { video_id } => Some(video_id),
-                _ => None,
-            })
-            // .filter_map(|search| search.try_into().ok())
-            // .map(Ok)
-            .collect();
-
-        let videos_resource: VideosResource = reqwest::Client::new()
-            .get("https://www.googleapis.com/youtube/v3/videos")
-            // FIXME add oAuth
-            // .header("Authorization", format!("Bearer {token}"))
-            // .query(&[("part", "id,snippet,fileDetails")])
-            .query(&[("part", "id,snippet,statistics,contentDetails")])
-            .query(&[("id", ids.join(","))])
-            .query(&[("maxResults", "25")])
-            .query(&[("key", token)])
-            .send()
-            .await?
-            .json()
-            .await?;
+    /// Fetches the details of the given videos.
+    pub(crate) async fn fetch_many(client: &Client, ids: &[String]) -> Result<Vec<Self>, Error> {
+        let mut videos = Vec::with_capacity(ids.len());
+
+        for ids in ids.chunks(MAX_PAGE_SIZE) {
+            let videos_resource: VideosResource = client
+                .get("videos")
+                .await?
+                .query(&[("part", "id,snippet,statistics,contentDetails")])
+                .query(&[("id", ids.join(","))])
+                .query(&[("maxResults", MAX_PAGE_SIZE)])
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            videos.extend(videos_resource.items.into_iter().map(Video::from));
+        }
 
-        Ok(videos_resource
-            .items
-            .into_iter()
-            .map(|resource| Video {
-                id: resource.id,
-                title: resource.snippet.title,
-                duration: resource.content_details.duration,
-            })
-            .collect())
+        Ok(videos)
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct ListResponse {
-    items: Vec<SearchResult>,
-}
-
-#[derive(Debug, Deserialize, PartialEq, Eq)]
-struct SearchResult {
-    id: Id,
-    snippet: Snippet,
+impl From<VideoResource> for Video {
+    fn from(resource: VideoResource) -> Self {
+        let statistics = resource.statistics.unwrap_or_default();
+
+        Self {
+            id: resource.id,
+            title: resource.snippet.title,
+            duration: resource.content_details.duration,
+            channel_title: resource.snippet.channel_title,
+            published_at: resource.snippet.published_at,
+            thumbnail: resource.snippet.thumbnails.best(),
+            tags: resource.snippet.tags,
+            view_count: statistics.view_count,
+            like_count: statistics.like_count,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -104,8 +79,31 @@ struct VideosResource {
 #[serde(rename_all = "camelCase")]
 struct VideoResource {
     id: String,
-    snippet: Snippet,
+    snippet: VideoSnippet,
     content_details: ContentDetails,
+    statistics: Option<VideoStatistics>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct VideoSnippet {
+    title: String,
+    #[serde(default)]
+    channel_title: String,
+    published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    thumbnails: Thumbnails,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct VideoStatistics {
+    #[serde(default, deserialize_with = "deserialize_count")]
+    view_count: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    like_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -123,72 +121,21 @@ where
     let duration = iso8601_duration::Duration::parse(&s).unwrap();
     Ok(duration.to_chrono().unwrap())
 }
-// impl Playlist {
-//     pub async fn from_ids(ids: Vec<String>) -> Self {
-//         let token = env!("YT_TOKEN");
-
-//         let response = reqwest::Client::new()
-//             .get("https://www.googleapis.com/youtube/v3/playlists")
-//             .query(&[("part", "id,snippet")])
-//             .query(&[("id", id.into_iter().join(","))])
-//             .query(&[("key", token)])
-//             .send()
-//             .await?;
-
-//     }
-// }
-
-// impl TryFrom<SearchResult> for Video {
-//     type Error = ();
-
-//     fn try_from(search: SearchResult) -> Result<Self, Self::Error> {
-//         let Id::Video { video_id: id } = search.id else {
-//             return Err(());
-//         };
 
-//         Ok(Self {
-//             id,
-//             title: search.snippet.title,
-//         })
-//     }
-// }
-
-// impl TryFrom<SearchResult> for Resource {
-//     type Error;
-
-//     fn try_from(search: SearchResult) -> Result<Self, Self::Error> {
-//         let result = match search.id {
-//             Id::Video { video_id } => Resource::Video {
-//                 id: video_id,
-//                 title: search.snippet.title,
-//             },
-//             Id::Channel { channel_id } => return Err(()),
-//             Id::Playlist { playlist_id } => Resource::Playlist { id: playlist_id, title: search.snippet.title, videos: () },
-//         }
-//     }
-// }
+/// Deserializes the decimal strings the API uses for statistics, e.g. `"15111123"`.
+pub(crate) fn deserialize_count<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|count| count.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[test]
-    fn parse_id() {
-        let json = r#"
-      {
-        "kind": "youtube#video",
-        "videoId": "1pW_j_eJIlo"
-      }"#;
-
-        let id: Id = serde_json::from_str(json).unwrap();
-        assert_eq!(
-            id,
-            Id::Video {
-                video_id: "1pW_j_eJIlo".to_string()
-            }
-        );
-    }
-
     #[test]
     fn parse_snippet() {
         let json = r#"{
@@ -228,70 +175,6 @@ mod test {
         );
     }
 
-    #[test]
-    fn parse_search_result() {
-        let json = r#"{
-      "kind": "youtube#searchListResponse",
-      "etag": "zKTHRvvYczZrAz9JayAYpOlPSws",
-      "nextPageToken": "CBkQAA",
-      "regionCode": "DE",
-      "pageInfo": {
-        "totalResults": 1000000,
-        "resultsPerPage": 25
-      },
-      "items": [
-        {
-          "kind": "youtube#searchResult",
-          "etag": "yJ-DyL6UtoYLkLxUZl1NriJnWYk",
-          "id": {
-            "kind": "youtube#video",
-            "videoId": "1pW_j_eJIlo"
-          },
-          "snippet": {
-            "publishedAt": "2025-04-16T19:12:18Z",
-            "channelId": "UCzH2vVrSpjwHNM0U3jJM0lQ",
-            "title": "Dark Blues Slide Guitar • &quot;Black Moon&quot;",
-            "description": "Unlock your guitar potential with exclusive lessons and tips at http://JustinJohnsonGuitar.com — from easy-to-follow basics to ...",
-            "thumbnails": {
-              "default": {
-                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/default.jpg",
-                "width": 120,
-                "height": 90
-              },
-              "medium": {
-                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/mqdefault.jpg",
-                "width": 320,
-                "height": 180
-              },
-              "high": {
-                "url": "https://i.ytimg.com/vi/1pW_j_eJIlo/hqdefault.jpg",
-                "width": 480,
-                "height": 360
-              }
-            },
-            "channelTitle": "Justin Johnson",
-            "liveBroadcastContent": "none",
-            "publishTime": "2025-04-16T19:12:18Z"
-          }
-        }
-      ]
-    }"#;
-
-        let result: ListResponse = serde_json::from_str(json).unwrap();
-        assert_eq!(
-            result.items[0],
-            SearchResult {
-                id: Id::Video {
-                    video_id: "1pW_j_eJIlo".to_string()
-                },
-                snippet: Snippet {
-                    title: "Dark Blues Slide Guitar • &quot;Black Moon&quot;".to_string(),
-                    description: "Unlock your guitar potential with exclusive lessons and tips at http://JustinJohnsonGuitar.com — from easy-to-follow basics to ...".to_string()
-                }
-            }
-        );
-    }
-
     #[test]
     fn parse_videos_resource() {
         let json = r#"
@@ -374,5 +257,19 @@ mod test {
 
         let result: VideosResource = serde_json::from_str(json).unwrap();
         assert_eq!(result.items.len(), 1);
+
+        let video = result.items.into_iter().map(Video::from).next().unwrap();
+        assert_eq!(video.channel_title, "Allman Brothers on MV");
+        assert_eq!(
+            video.published_at.map(|date| date.to_rfc3339()).as_deref(),
+            Some("2014-09-11T17:03:26+00:00")
+        );
+        assert_eq!(
+            video.thumbnail.map(|thumbnail| thumbnail.url).as_deref(),
+            Some("https://i.ytimg.com/vi/FUvxRjYqjEQ/sddefault.jpg")
+        );
+        assert_eq!(video.tags.len(), 8);
+        assert_eq!(video.view_count, Some(15111123));
+        assert_eq!(video.like_count, Some(114163));
     }
 }
@@ -0,0 +1,78 @@
+//! A minimal HTTP server answering with canned responses, shared by the
+//! integration tests and the unit tests of the crate.
+
+// Each test crate only uses a part of it.
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// E.g. `/search?q=blues`.
+    pub target: String,
+    /// The form or JSON body, empty for `GET` requests.
+    pub body: String,
+}
+
+/// Answers requests with the given responses in order, recording them.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<(u16, &'static str)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for ((status, body), stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                    line.clear();
+                }
+
+                // Read the body as well, closing with unread data resets the connection.
+                let mut request_body = vec![0; length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                let target = request_line.split(' ').nth(1).unwrap_or_default();
+                recorded.lock().unwrap().push(Request {
+                    target: target.to_string(),
+                    body: String::from_utf8(request_body).unwrap(),
+                });
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
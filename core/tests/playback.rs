@@ -0,0 +1,286 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use player_core::backend::{self, Event, Fixtures, Options, OutputKind};
+use player_core::{Backend, Error, Video};
+
+const SAMPLE_RATE: u32 = 44_100;
+const LENGTH: Duration = Duration::from_secs(3);
+
+/// How much faster than real time the tracks play.
+const SPEED: f32 = 4.0;
+
+/// A folder of its own for each test, removed once it is done.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("player-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes a mono 16 bit WAV file with a 440 Hz sine of [`LENGTH`].
+fn write_sine(path: &Path) {
+    let samples = SAMPLE_RATE * LENGTH.as_secs() as u32;
+    let data_len = samples * 2;
+
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    for i in 0..samples {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.2;
+        wav.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+    }
+
+    std::fs::write(path, wav).unwrap();
+}
+
+fn video(id: &str) -> Video {
+    Video {
+        id: id.to_string(),
+        title: id.to_string(),
+        duration: chrono::Duration::seconds(LENGTH.as_secs() as i64),
+        channel_title: String::new(),
+        published_at: None,
+        thumbnail: None,
+        tags: Vec::new(),
+        view_count: None,
+        like_count: None,
+    }
+}
+
+/// Starts a backend serving the videos "sine" and "second" from a fixture,
+/// playing on a null output.
+async fn start(dir: &TempDir) -> (Backend, impl Stream<Item = Event> + Unpin) {
+    let fixture = dir.0.join("sine.wav");
+    write_sine(&fixture);
+
+    let extractor = Fixtures::default()
+        .with("sine", &fixture)
+        .with("second", &fixture);
+    let mut events = Box::pin(backend::start_with(Options {
+        extractor: Arc::new(extractor),
+        cache_dir: dir.0.join("cache"),
+        output: OutputKind::Null { speed: SPEED },
+    }));
+
+    match events.next().await {
+        Some(Event::Started(backend)) => (backend, events),
+        event => panic!("unexpected event {event:?}"),
+    }
+}
+
+/// Waits for the first event `f` returns something for.
+async fn wait_for<T>(
+    events: &mut (impl Stream<Item = Event> + Unpin),
+    mut f: impl FnMut(Event) -> Option<T>,
+) -> T {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(event) = events.next().await {
+            if let Some(value) = f(event) {
+                return value;
+            }
+        }
+
+        panic!("backend stopped");
+    })
+    .await
+    .expect("timed out waiting for event")
+}
+
+#[tokio::test]
+async fn play_fixture() {
+    let dir = TempDir::new("play");
+    let (backend, mut events) = start(&dir).await;
+
+    let info = backend.load_and_play(video("sine").into()).await.unwrap();
+    assert_eq!(info.duration, Some(LENGTH));
+
+    assert_eq!(next_track(&mut events).await, "sine");
+
+    wait_for(&mut events, |event| match event {
+        Event::PlaybackPosition(pos) if pos > Duration::ZERO => Some(()),
+        _ => None,
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn seek_fixture() {
+    let dir = TempDir::new("seek");
+    let (backend, mut events) = start(&dir).await;
+
+    backend
+        .clone()
+        .load_and_play(video("sine").into())
+        .await
+        .unwrap();
+    backend.seek_to(Duration::from_secs(2)).await.unwrap();
+
+    wait_for(&mut events, |event| match event {
+        Event::PlaybackPosition(pos) if pos >= Duration::from_secs(2) => Some(()),
+        _ => None,
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn report_downloaded() {
+    let dir = TempDir::new("downloaded");
+    let (backend, mut events) = start(&dir).await;
+
+    backend.load_and_play(video("sine").into()).await.unwrap();
+
+    wait_for(&mut events, |event| match event {
+        Event::Downloaded(fraction) if fraction == 1.0 => Some(()),
+        _ => None,
+    })
+    .await;
+}
+
+/// The track started next.
+async fn next_track(events: &mut (impl Stream<Item = Event> + Unpin)) -> String {
+    wait_for(events, |event| match event {
+        Event::TrackStarted(track, _) => Some(track.id().into_owned()),
+        _ => None,
+    })
+    .await
+}
+
+#[tokio::test]
+async fn pause_fixture() {
+    let dir = TempDir::new("pause");
+    let (backend, mut events) = start(&dir).await;
+
+    backend
+        .clone()
+        .load_and_play(video("sine").into())
+        .await
+        .unwrap();
+    wait_for(&mut events, |event| match event {
+        Event::PlaybackPosition(pos) if pos > Duration::ZERO => Some(()),
+        _ => None,
+    })
+    .await;
+
+    backend.clone().pause().await.unwrap();
+
+    // Long enough to play the whole track if it didn't pause.
+    let _ = tokio::time::timeout(LENGTH.div_f32(SPEED) * 2, async {
+        while let Some(event) = events.next().await {
+            assert!(
+                !matches!(event, Event::TrackEnded),
+                "played on while paused"
+            );
+        }
+    })
+    .await;
+
+    backend.play().await.unwrap();
+    wait_for(&mut events, |event| match event {
+        Event::TrackEnded => Some(()),
+        _ => None,
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn play_queue() {
+    let dir = TempDir::new("queue");
+    let (backend, mut events) = start(&dir).await;
+
+    backend
+        .play_all(vec![video("sine").into(), video("second").into()])
+        .await
+        .unwrap();
+
+    assert_eq!(next_track(&mut events).await, "sine");
+    assert_eq!(next_track(&mut events).await, "second");
+}
+
+#[tokio::test]
+async fn skip_in_queue() {
+    let dir = TempDir::new("skip");
+    let (backend, mut events) = start(&dir).await;
+
+    backend
+        .clone()
+        .play_all(vec![video("sine").into(), video("second").into()])
+        .await
+        .unwrap();
+    assert_eq!(next_track(&mut events).await, "sine");
+
+    backend.skip().await.unwrap();
+
+    // The position reported as the track ends tells where it was cut off.
+    let mut position = Duration::ZERO;
+    wait_for(&mut events, |event| match event {
+        Event::PlaybackPosition(pos) => {
+            position = pos;
+            None
+        }
+        Event::TrackEnded => Some(()),
+        _ => None,
+    })
+    .await;
+    assert!(
+        position < LENGTH - Duration::from_millis(500),
+        "played to {position:?} before skipping"
+    );
+
+    assert_eq!(next_track(&mut events).await, "second");
+}
+
+#[tokio::test]
+async fn play_unknown_video() {
+    let dir = TempDir::new("unknown");
+    let (backend, _events) = start(&dir).await;
+
+    let result = backend.load_and_play(video("missing").into()).await;
+    assert!(matches!(result, Err(Error::NotFound(id)) if id == "missing"));
+}
+
+#[tokio::test]
+async fn play_after_stop() {
+    let dir = TempDir::new("stopped");
+    let (backend, events) = start(&dir).await;
+
+    // Dropping the events stops the player, which may still take a command meanwhile.
+    drop(events);
+
+    let error = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match backend.clone().load_and_play(video("sine").into()).await {
+                Ok(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                Err(error) => break error,
+            }
+        }
+    })
+    .await
+    .expect("player kept running");
+
+    assert!(matches!(error, Error::PlayerStopped));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::MockServer;
+use player_core::{Error, Invidious, Piped, SearchItem, SearchPage, search};
+
+fn targets(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .map(|request| request.target)
+        .collect()
+}
+
+fn video_ids(page: &SearchPage) -> Vec<&str> {
+    page.items
+        .iter()
+        .filter_map(|item| match item {
+            SearchItem::Video(video) => Some(video.id.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn search_invidious() {
+    let server = MockServer::start(vec![
+        (
+            200,
+            r#"[
+              { "type": "video", "videoId": "a", "title": "A", "lengthSeconds": 60 },
+              { "type": "channel", "author": "Someone", "authorId": "UC" },
+              { "type": "video", "videoId": "b", "title": "B", "lengthSeconds": 120 }
+            ]"#,
+        ),
+        (200, "[]"),
+    ]);
+
+    let provider = Arc::new(Invidious::new(format!("{}/", server.url)));
+    let page = search(provider, "slide guitar".to_string()).await.unwrap();
+    assert_eq!(video_ids(&page), ["a", "b"]);
+    assert_eq!(page.total_results, None);
+
+    let page = page.next.unwrap().fetch().await.unwrap();
+    assert!(page.items.is_empty());
+    assert!(page.next.is_none());
+
+    assert_eq!(
+        targets(&server),
+        [
+            "/api/v1/search?q=slide+guitar&type=video&page=1",
+            "/api/v1/search?q=slide+guitar&type=video&page=2"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn search_piped() {
+    let server = MockServer::start(vec![
+        (
+            200,
+            r#"{
+              "items": [
+                { "type": "stream", "url": "/watch?v=a", "title": "A", "duration": 60 },
+                { "type": "playlist", "url": "/playlist?list=PL", "name": "Mix" }
+              ],
+              "nextpage": "token"
+            }"#,
+        ),
+        (
+            200,
+            r#"{
+              "items": [
+                { "type": "stream", "url": "/watch?v=b", "title": "B", "duration": -1 }
+              ],
+              "nextpage": null
+            }"#,
+        ),
+    ]);
+
+    let provider = Arc::new(Piped::new(&server.url));
+    let page = search(provider, "blues".to_string()).await.unwrap();
+    assert_eq!(video_ids(&page), ["a"]);
+
+    let page = page.next.unwrap().fetch().await.unwrap();
+    assert_eq!(video_ids(&page), ["b"]);
+    assert!(page.next.is_none());
+
+    assert_eq!(
+        targets(&server),
+        [
+            "/search?q=blues&filter=videos",
+            "/nextpage/search?nextpage=token&q=blues&filter=videos"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn search_fails_on_server_error() {
+    let server = MockServer::start(vec![(502, r#"{ "error": "upstream" }"#)]);
+
+    let provider = Arc::new(Piped::new(&server.url));
+    let result = search(provider, "blues".to_string()).await;
+    assert!(matches!(result, Err(Error::RequestFailed(_))));
+}
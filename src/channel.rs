@@ -0,0 +1,244 @@
+use iced::{
+    Alignment, Element, Length, Task,
+    widget::{button, column, container, horizontal_space, image, row, scrollable, text},
+};
+use player_core::{Channel, Client, Error, Playlist, UploadsCursor, UploadsPage, Video};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ThumbnailLoaded(Result<Vec<u8>, Error>),
+    UploadsLoaded(Result<UploadsPage, Error>),
+    Scrolled(f32),
+    MoreUploadsLoaded(Result<UploadsPage, Error>),
+    PlaylistsLoaded(Result<Vec<Playlist>, Error>),
+    VideoSelected(usize),
+    PlaylistSelected(usize),
+    BackPressed,
+}
+
+/// What the channel page asks the app to do.
+pub enum Action {
+    None,
+    Play(Video),
+    PlayPlaylist(Playlist),
+    Back,
+    Run(Task<Message>),
+}
+
+pub struct Page {
+    channel: Channel,
+    thumbnail: Option<image::Handle>,
+    uploads: Content<Video>,
+    next_uploads: Option<UploadsCursor>,
+    is_loading_more: bool,
+    playlists: Content<Playlist>,
+}
+
+enum Content<T> {
+    Loading,
+    Loaded(Vec<T>),
+    Failed(Error),
+}
+
+impl<T> From<Result<Vec<T>, Error>> for Content<T> {
+    fn from(result: Result<Vec<T>, Error>) -> Self {
+        match result {
+            Ok(items) => Self::Loaded(items),
+            Err(err) => {
+                tracing::warn!("loading the channel failed: {err}");
+                Self::Failed(err)
+            }
+        }
+    }
+}
+
+impl Page {
+    pub fn new(channel: Channel, client: Client) -> (Self, Task<Message>) {
+        let thumbnail = match channel.thumbnail.clone() {
+            Some(thumbnail) => Task::perform(thumbnail.download(), Message::ThumbnailLoaded),
+            None => Task::none(),
+        };
+
+        let uploads = Task::perform(
+            channel.clone().uploads(client.clone()),
+            Message::UploadsLoaded,
+        );
+        let playlists = Task::perform(channel.clone().playlists(client), Message::PlaylistsLoaded);
+
+        (
+            Self {
+                channel,
+                thumbnail: None,
+                uploads: Content::Loading,
+                next_uploads: None,
+                is_loading_more: false,
+                playlists: Content::Loading,
+            },
+            Task::batch([thumbnail, uploads, playlists]),
+        )
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::ThumbnailLoaded(Ok(bytes)) => {
+                self.thumbnail = Some(image::Handle::from_bytes(bytes));
+
+                Action::None
+            }
+            Message::ThumbnailLoaded(Err(err)) => {
+                tracing::warn!("loading the channel thumbnail failed: {err}");
+
+                Action::None
+            }
+            Message::UploadsLoaded(result) => {
+                self.uploads = result
+                    .map(|page| {
+                        self.next_uploads = page.next;
+                        page.videos
+                    })
+                    .into();
+
+                Action::None
+            }
+            Message::Scrolled(offset) => {
+                // Start loading before the user actually hits the bottom.
+                if offset < 0.9 || self.is_loading_more {
+                    return Action::None;
+                }
+
+                // Keep the cursor until the page arrived, so a failed request can be retried.
+                let Some(cursor) = self.next_uploads.clone() else {
+                    return Action::None;
+                };
+
+                self.is_loading_more = true;
+
+                Action::Run(Task::perform(cursor.fetch(), Message::MoreUploadsLoaded))
+            }
+            Message::MoreUploadsLoaded(result) => {
+                self.is_loading_more = false;
+
+                match result {
+                    Ok(page) => {
+                        if let Content::Loaded(videos) = &mut self.uploads {
+                            videos.extend(page.videos);
+                        }
+
+                        self.next_uploads = page.next;
+                    }
+                    // Scrolling down again retries with the same cursor.
+                    Err(err) => tracing::warn!("loading more uploads failed: {err}"),
+                }
+
+                Action::None
+            }
+            Message::PlaylistsLoaded(result) => {
+                self.playlists = result.into();
+
+                Action::None
+            }
+            Message::VideoSelected(index) => match &self.uploads {
+                Content::Loaded(videos) => videos
+                    .get(index)
+                    .cloned()
+                    .map_or(Action::None, Action::Play),
+                _ => Action::None,
+            },
+            Message::PlaylistSelected(index) => match &self.playlists {
+                Content::Loaded(playlists) => playlists
+                    .get(index)
+                    .cloned()
+                    .map_or(Action::None, Action::PlayPlaylist),
+                _ => Action::None,
+            },
+            Message::BackPressed => Action::Back,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let subscribers = match self.channel.subscriber_count {
+            Some(count) => format!("{count} subscribers"),
+            None => "Subscribers hidden".to_string(),
+        };
+
+        let mut header = row![button("Back").on_press(Message::BackPressed)]
+            .spacing(10)
+            .align_y(Alignment::Center);
+
+        if let Some(thumbnail) = &self.thumbnail {
+            header = header.push(image(thumbnail.clone()).width(48).height(48));
+        }
+
+        let header = header.push(column![
+            text(&self.channel.title).size(20),
+            text(subscribers).size(12)
+        ]);
+
+        let playlists = section("Playlists", &self.playlists, |i, playlist| {
+            button(
+                row![
+                    text(&playlist.title),
+                    horizontal_space(),
+                    text!("{} videos", playlist.item_count)
+                ]
+                .align_y(Alignment::Center),
+            )
+            .on_press(Message::PlaylistSelected(i))
+            .width(Length::Fill)
+            .style(button::secondary)
+            .into()
+        });
+
+        let uploads = section("Uploads", &self.uploads, |i, video| {
+            button(
+                row![
+                    text(&video.title),
+                    horizontal_space(),
+                    text!(
+                        "{:02}:{:02}:{:02}",
+                        video.duration.num_hours(),
+                        video.duration.num_minutes() % 60,
+                        video.duration.num_seconds() % 60
+                    )
+                ]
+                .align_y(Alignment::Center),
+            )
+            .on_press(Message::VideoSelected(i))
+            .width(Length::Fill)
+            .style(button::secondary)
+            .into()
+        });
+
+        let mut content = column![playlists, uploads].spacing(20);
+
+        if self.is_loading_more {
+            content = content.push(container(text("Loading more...")).center_x(Length::Fill));
+        }
+
+        column![
+            header,
+            scrollable(content)
+                .height(Length::Fill)
+                .on_scroll(|viewport| Message::Scrolled(viewport.relative_offset().y))
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+fn section<'a, T>(
+    title: &'a str,
+    content: &'a Content<T>,
+    view: impl Fn(usize, &'a T) -> Element<'a, Message>,
+) -> Element<'a, Message> {
+    let content: Element<_> = match content {
+        Content::Loading => text("Loading...").into(),
+        Content::Failed(err) => text(err.to_string()).into(),
+        Content::Loaded(items) if items.is_empty() => text("Nothing here.").into(),
+        Content::Loaded(items) => column(items.iter().enumerate().map(|(i, item)| view(i, item)))
+            .spacing(5)
+            .into(),
+    };
+
+    container(column![text(title).size(16), content].spacing(5)).into()
+}
@@ -0,0 +1,85 @@
+use iced::{
+    Alignment, Element, Length,
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+};
+use player_core::Video;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    VideoSelected(usize),
+    RemovePressed(usize),
+    BackPressed,
+}
+
+/// What the downloads page asks the app to do.
+pub enum Action {
+    None,
+    Play(Video),
+    Remove(String),
+    Back,
+}
+
+pub fn update(message: Message, downloads: &[Video]) -> Action {
+    match message {
+        Message::VideoSelected(index) => downloads
+            .get(index)
+            .cloned()
+            .map_or(Action::None, Action::Play),
+        Message::RemovePressed(index) => downloads
+            .get(index)
+            .map_or(Action::None, |video| Action::Remove(video.id.clone())),
+        Message::BackPressed => Action::Back,
+    }
+}
+
+pub fn view(downloads: &[Video]) -> Element<'_, Message> {
+    let content: Element<_> = if downloads.is_empty() {
+        container(text("Nothing downloaded yet."))
+            .center(Length::Fill)
+            .into()
+    } else {
+        scrollable(
+            column(downloads.iter().enumerate().map(|(i, video)| {
+                row![
+                    button(
+                        row![
+                            text(&video.title),
+                            horizontal_space(),
+                            text!(
+                                "{:02}:{:02}:{:02}",
+                                video.duration.num_hours(),
+                                video.duration.num_minutes() % 60,
+                                video.duration.num_seconds() % 60
+                            )
+                        ]
+                        .align_y(Alignment::Center)
+                    )
+                    .on_press(Message::VideoSelected(i))
+                    .width(Length::Fill)
+                    .style(button::secondary),
+                    button("Remove")
+                        .on_press(Message::RemovePressed(i))
+                        .style(button::text)
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(5),
+        )
+        .spacing(5)
+        .into()
+    };
+
+    column![
+        row![
+            button("Back").on_press(Message::BackPressed),
+            text("Downloads").size(20)
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        content
+    ]
+    .spacing(10)
+    .into()
+}
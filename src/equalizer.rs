@@ -0,0 +1,163 @@
+use iced::{
+    Alignment, Element, Length,
+    widget::{
+        button, column, container, pick_list, row, text, text_input, toggler, vertical_slider,
+    },
+};
+use player_core::Preferences;
+use player_core::backend::{FREQUENCIES, Gains, Preset};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Toggled(bool),
+    GainChanged(usize, f32),
+    PresetSelected(String),
+    PresetNameChanged(String),
+    SavePressed,
+    DeletePressed(String),
+    BackPressed,
+}
+
+/// What the equalizer panel asks the app to do.
+pub enum Action {
+    None,
+    SetEqualizer { enabled: bool, gains: Gains },
+    SavePreset(Preset),
+    DeletePreset(String),
+    Back,
+}
+
+#[derive(Default)]
+pub struct Panel {
+    preset_name: String,
+}
+
+impl Panel {
+    pub fn update(&mut self, message: Message, preferences: &Preferences) -> Action {
+        match message {
+            Message::Toggled(enabled) => Action::SetEqualizer {
+                enabled,
+                gains: preferences.equalizer_gains,
+            },
+            Message::GainChanged(band, gain) => {
+                let mut gains = preferences.equalizer_gains;
+                gains[band] = gain;
+
+                Action::SetEqualizer {
+                    enabled: preferences.equalizer,
+                    gains,
+                }
+            }
+            Message::PresetSelected(name) => presets(preferences)
+                .find(|preset| preset.name == name)
+                .map_or(Action::None, |preset| Action::SetEqualizer {
+                    enabled: true,
+                    gains: preset.gains,
+                }),
+            Message::PresetNameChanged(name) => {
+                self.preset_name = name;
+
+                Action::None
+            }
+            Message::SavePressed => {
+                let name = std::mem::take(&mut self.preset_name);
+                let name = name.trim();
+
+                if name.is_empty() {
+                    return Action::None;
+                }
+
+                Action::SavePreset(Preset::new(name, preferences.equalizer_gains))
+            }
+            Message::DeletePressed(name) => Action::DeletePreset(name),
+            Message::BackPressed => Action::Back,
+        }
+    }
+
+    pub fn view<'a>(&'a self, preferences: &'a Preferences) -> Element<'a, Message> {
+        let selected = presets(preferences)
+            .find(|preset| preset.gains == preferences.equalizer_gains)
+            .map(|preset| preset.name);
+        let names: Vec<String> = presets(preferences).map(|preset| preset.name).collect();
+
+        let delete = selected
+            .clone()
+            .filter(|name| {
+                preferences
+                    .presets
+                    .iter()
+                    .any(|preset| &preset.name == name)
+            })
+            .map(Message::DeletePressed);
+
+        let presets = row![
+            pick_list(names, selected, Message::PresetSelected)
+                .placeholder("Custom")
+                .width(Length::Fill),
+            button("Delete")
+                .on_press_maybe(delete)
+                .style(button::secondary),
+            text_input("Preset name", &self.preset_name)
+                .on_input(Message::PresetNameChanged)
+                .on_submit(Message::SavePressed)
+                .width(200),
+            button("Save").on_press_maybe(
+                (!self.preset_name.trim().is_empty()).then_some(Message::SavePressed)
+            )
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let bands = row(FREQUENCIES
+            .iter()
+            .zip(preferences.equalizer_gains)
+            .enumerate()
+            .map(|(band, (&frequency, gain))| {
+                column![
+                    text!("{gain:+.1}").size(12),
+                    vertical_slider(-12.0..=12.0, gain, move |gain| {
+                        Message::GainChanged(band, gain)
+                    })
+                    .step(0.5)
+                    .height(160),
+                    text(format_frequency(frequency)).size(12)
+                ]
+                .spacing(5)
+                .align_x(Alignment::Center)
+                .width(Length::Fill)
+                .into()
+            }))
+        .spacing(5);
+
+        column![
+            row![
+                button("Back").on_press(Message::BackPressed),
+                text("Equalizer").size(20),
+                toggler(preferences.equalizer)
+                    .label("Enabled")
+                    .on_toggle(Message::Toggled)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            presets,
+            container(bands).padding(10)
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+/// The builtin presets followed by the ones saved by the user.
+fn presets(preferences: &Preferences) -> impl Iterator<Item = Preset> + '_ {
+    Preset::builtin()
+        .into_iter()
+        .chain(preferences.presets.iter().cloned())
+}
+
+fn format_frequency(frequency: f32) -> String {
+    if frequency >= 1000.0 {
+        format!("{}k", frequency / 1000.0)
+    } else {
+        format!("{frequency}")
+    }
+}
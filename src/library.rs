@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+
+use iced::{
+    Alignment, Element, Length, Task,
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+};
+use player_core::track::EXTENSIONS;
+use player_core::{Error, LocalFile};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Scanned(Result<Vec<LocalFile>, Error>),
+    FileSelected(usize),
+    EnqueuePressed(usize),
+    PlayAllPressed,
+    ChooseFolderPressed,
+    RescanPressed,
+    BackPressed,
+}
+
+/// What the library page asks the app to do.
+pub enum Action {
+    None,
+    Play(LocalFile),
+    Enqueue(LocalFile),
+    PlayAll(Vec<LocalFile>),
+    ChooseFolder,
+    Rescan,
+    Back,
+}
+
+pub struct Page {
+    folder: Option<PathBuf>,
+    files: Files,
+}
+
+enum Files {
+    Loading,
+    Loaded(Vec<LocalFile>),
+    Failed(Error),
+}
+
+impl Page {
+    /// Shows the audio files in `folder`, if one was chosen.
+    pub fn new(folder: Option<PathBuf>) -> (Self, Task<Message>) {
+        let (files, task) = match folder.clone() {
+            Some(folder) => (
+                Files::Loading,
+                Task::perform(LocalFile::scan(folder), Message::Scanned),
+            ),
+            None => (Files::Loaded(Vec::new()), Task::none()),
+        };
+
+        (Self { folder, files }, task)
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Scanned(Ok(files)) => {
+                self.files = Files::Loaded(files);
+
+                Action::None
+            }
+            Message::Scanned(Err(err)) => {
+                tracing::warn!("scanning the library failed: {err}");
+                self.files = Files::Failed(err);
+
+                Action::None
+            }
+            Message::FileSelected(index) => self.file(index).map_or(Action::None, Action::Play),
+            Message::EnqueuePressed(index) => {
+                self.file(index).map_or(Action::None, Action::Enqueue)
+            }
+            Message::PlayAllPressed => match &self.files {
+                Files::Loaded(files) if !files.is_empty() => Action::PlayAll(files.clone()),
+                _ => Action::None,
+            },
+            Message::ChooseFolderPressed => Action::ChooseFolder,
+            Message::RescanPressed => Action::Rescan,
+            Message::BackPressed => Action::Back,
+        }
+    }
+
+    fn file(&self, index: usize) -> Option<LocalFile> {
+        match &self.files {
+            Files::Loaded(files) => files.get(index).cloned(),
+            _ => None,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let folder = match &self.folder {
+            Some(folder) => text(folder.display().to_string()).style(text::secondary),
+            None => text("No folder chosen").style(text::secondary),
+        };
+
+        let has_files = matches!(&self.files, Files::Loaded(files) if !files.is_empty());
+
+        let header = row![
+            button("Back").on_press(Message::BackPressed),
+            text("Library").size(20),
+            folder,
+            horizontal_space(),
+            button("Play all").on_press_maybe(has_files.then_some(Message::PlayAllPressed)),
+            button("Rescan")
+                .on_press_maybe(self.folder.is_some().then_some(Message::RescanPressed))
+                .style(button::secondary),
+            button("Choose folder")
+                .on_press(Message::ChooseFolderPressed)
+                .style(button::secondary)
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let content: Element<_> = match &self.files {
+            Files::Loading => text("Scanning...").into(),
+            Files::Failed(err) => text(err.to_string()).into(),
+            Files::Loaded(files) if files.is_empty() => {
+                text!("No audio files ({}) found.", EXTENSIONS.join(", ")).into()
+            }
+            Files::Loaded(files) => scrollable(
+                column(files.iter().enumerate().map(|(i, file)| {
+                    row![
+                        button(text(&file.title))
+                            .on_press(Message::FileSelected(i))
+                            .width(Length::Fill)
+                            .style(button::secondary),
+                        button("Queue")
+                            .on_press(Message::EnqueuePressed(i))
+                            .style(button::text)
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .into()
+                }))
+                .spacing(5),
+            )
+            .spacing(5)
+            .into(),
+        };
+
+        column![header, container(content).height(Length::Fill)]
+            .spacing(10)
+            .into()
+    }
+}
+
+/// Lets the user pick audio files to play.
+pub async fn pick_files() -> Vec<LocalFile> {
+    rfd::AsyncFileDialog::new()
+        .add_filter("Audio", EXTENSIONS)
+        .pick_files()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file| LocalFile::new(file.path()))
+        .collect()
+}
+
+/// Lets the user pick the folder of the library.
+pub async fn pick_folder() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .pick_folder()
+        .await
+        .map(|folder| folder.path().to_path_buf())
+}
@@ -1,15 +1,34 @@
+mod channel;
+mod downloads;
+mod equalizer;
+mod library;
 mod player;
+mod queue;
+mod settings;
 use player::Player;
 
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 use iced::widget::{
-    button, column, container, horizontal_rule, horizontal_space, row, scrollable, text, text_input,
+    button, column, container, horizontal_rule, horizontal_space, row, scrollable, text,
+    text_input, tooltip,
 };
 use iced::{Alignment, Element, Length, Subscription, Task, Theme};
-use player_core::{Error, Video, backend};
+use player_core::backend::{Format, Queue};
+use player_core::oauth::DeviceAuthorization;
+use player_core::{
+    Client, Config, Error, Invidious, LocalFile, Piped, Playlist, Preferences, ProviderKind,
+    SearchCursor, SearchItem, SearchPage, SearchProvider, Track, Video, backend, search,
+};
 
 fn main() -> iced::Result {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
     iced::application(App::new, App::update, App::view)
         .title(App::title)
         // .font(icon::FONT)
@@ -22,23 +41,89 @@ fn main() -> iced::Result {
 enum Message {
     SearchChanged(String),
     SearchCooled,
-    VideosListed(Result<Vec<Video>, Error>),
-    VideoSelected(usize),
+    PreferencesCooled,
+    VideosListed(Result<SearchPage, Error>),
+    ResultsScrolled(f32),
+    RetryPagePressed,
+    MoreVideosListed(usize, Result<SearchPage, Error>),
+    ResultSelected(usize),
+    EnqueuePressed(usize),
+    DownloadPressed(usize),
+    DownloadsPressed,
+    Downloads(downloads::Message),
+    PlaylistLoaded(Result<Vec<Video>, Error>),
+    Channel(channel::Message),
+    OpenFilePressed,
+    FilesPicked(Vec<LocalFile>),
+    LibraryPressed,
+    Library(library::Message),
+    LibraryFolderPicked(Option<PathBuf>),
+    SettingsPressed,
+    Settings(settings::Message),
+    EqualizerPressed,
+    Equalizer(equalizer::Message),
+    LoginChecked(bool),
+    LoginPressed,
+    DeviceCodeReceived(Result<DeviceAuthorization, Error>),
+    LoggedIn(Result<(), Error>),
+    LogoutPressed,
+    LoggedOut(Result<(), Error>),
+    CommandFailed(Error),
     Backend(backend::Event),
     Player(player::Message),
+    Queue(queue::Message),
 }
 
 struct App {
+    client: Result<Client, Error>,
+    login: Login,
+    preferences: Preferences,
+    /// Pending changes of the preferences, saved once they cool down.
+    preferences_temperature: usize,
+
     search: String,
     search_temperature: usize,
     is_searching: bool,
+    error: Option<Error>,
+    /// Why nothing can be heard, until the output device is back.
+    device_error: Option<Error>,
+    /// Why the output device chosen in the settings isn't played on.
+    output_device_error: Option<Error>,
+    /// Why the last command didn't reach the player.
+    command_error: Option<Error>,
 
-    videos: Vec<Video>,
+    results: Vec<SearchItem>,
+    total_results: Option<u64>,
+    next_page: Option<SearchCursor>,
+    /// Counts the searches, so pages of an older one are not appended to the results.
+    search_generation: usize,
+    is_loading_more: bool,
+    /// Why the next page of results failed to load, shown below them.
+    page_error: Option<Error>,
+    channel: Option<channel::Page>,
+    library: Option<library::Page>,
+    is_showing_downloads: bool,
+    settings: Option<settings::Page>,
+    equalizer: Option<equalizer::Panel>,
     player: Option<Player>,
+    queue: Queue,
+    /// The videos downloaded for offline use.
+    downloads: Vec<Video>,
+    /// The ids of the videos being downloaded.
+    downloading: HashSet<String>,
+    /// Why downloading a video failed by its id, shown in place of its download button.
+    failed_downloads: HashMap<String, Error>,
 
     backend: Backend,
 }
 
+enum Login {
+    LoggedOut,
+    Requesting,
+    Pending(DeviceAuthorization),
+    LoggedIn,
+}
+
 enum Backend {
     Starting,
     Started(player_core::Backend),
@@ -46,17 +131,51 @@ enum Backend {
 
 impl App {
     pub fn new() -> (Self, Task<Message>) {
+        let client = Config::load().map(Client::new);
+        let preferences = Preferences::load().unwrap_or_else(|err| {
+            tracing::warn!("loading the preferences failed, using the defaults: {err}");
+            Preferences::default()
+        });
+
+        let task = match &client {
+            Ok(client) => Task::perform(client.clone().is_logged_in(), Message::LoginChecked),
+            Err(_) => Task::none(),
+        };
+
         (
             Self {
+                client,
+                login: Login::LoggedOut,
+                preferences,
+                preferences_temperature: 0,
+
                 search: String::new(),
                 search_temperature: 0,
                 is_searching: false,
+                error: None,
+                device_error: None,
+                output_device_error: None,
+                command_error: None,
 
-                videos: Vec::new(),
+                results: Vec::new(),
+                total_results: None,
+                next_page: None,
+                search_generation: 0,
+                is_loading_more: false,
+                page_error: None,
+                channel: None,
+                library: None,
+                is_showing_downloads: false,
+                settings: None,
+                equalizer: None,
                 player: None,
+                queue: Queue::default(),
+                downloads: Vec::new(),
+                downloading: HashSet::new(),
+                failed_downloads: HashMap::new(),
                 backend: Backend::Starting,
             },
-            Task::none(),
+            task,
         )
     }
 
@@ -74,29 +193,118 @@ impl App {
                     Message::SearchCooled
                 })
             }
+            Message::PreferencesCooled => {
+                self.preferences_temperature = self.preferences_temperature.saturating_sub(1);
+
+                if self.preferences_temperature == 0 {
+                    self.flush_preferences();
+                }
+
+                Task::none()
+            }
             Message::SearchCooled => {
                 self.search_temperature = self.search_temperature.saturating_sub(1);
 
-                if self.search_temperature == 0 {
-                    self.is_searching = true;
+                if self.search_temperature > 0 {
+                    return Task::none();
+                }
 
-                    Task::perform(Video::search(self.search.clone()), Message::VideosListed)
-                } else {
-                    Task::none()
+                self.is_searching = true;
+                self.is_loading_more = false;
+                self.page_error = None;
+                self.search_generation += 1;
+                self.channel = None;
+                self.library = None;
+                self.is_showing_downloads = false;
+                self.settings = None;
+                self.equalizer = None;
+
+                match self.search_provider() {
+                    Ok(provider) => {
+                        Task::perform(search(provider, self.search.clone()), Message::VideosListed)
+                    }
+                    Err(err) => Task::done(Message::VideosListed(Err(err))),
                 }
             }
-            Message::VideosListed(Ok(videos)) => {
+            Message::VideosListed(Ok(page)) => {
                 self.is_searching = false;
-                self.videos = videos;
+                self.error = None;
+                self.results = page.items;
+                self.total_results = page.total_results;
+                self.next_page = page.next;
 
                 Task::none()
             }
             Message::VideosListed(Err(err)) => {
-                dbg!(err);
+                tracing::warn!("searching failed: {err}");
+                self.is_searching = false;
+                self.error = Some(err);
+
                 Task::none()
             }
-            Message::VideoSelected(index) => {
-                let Some(video) = &self.videos.get(index).cloned() else {
+            Message::ResultsScrolled(offset) => {
+                // Start loading before the user actually hits the bottom. After
+                // a failure it waits for the user to retry.
+                if offset < 0.9 || self.is_loading_more || self.page_error.is_some() {
+                    return Task::none();
+                }
+
+                self.load_more()
+            }
+            Message::RetryPagePressed => {
+                self.page_error = None;
+
+                self.load_more()
+            }
+            Message::MoreVideosListed(generation, result) => {
+                // A new search was started in the meantime.
+                if generation != self.search_generation {
+                    return Task::none();
+                }
+
+                self.is_loading_more = false;
+
+                match result {
+                    Ok(page) => {
+                        self.results.extend(page.items);
+                        self.next_page = page.next;
+                    }
+                    Err(err) => {
+                        tracing::warn!("loading more results failed: {err}");
+                        self.page_error = Some(err);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::ResultSelected(index) => match self.results.get(index).cloned() {
+                Some(SearchItem::Video(video)) => self.play(video.into()),
+                Some(SearchItem::Playlist(playlist)) => self.play_playlist(playlist),
+                Some(SearchItem::Channel(channel)) => {
+                    let Ok(client) = &self.client else {
+                        return Task::none();
+                    };
+
+                    let (page, task) = channel::Page::new(channel, client.clone());
+                    self.channel = Some(page);
+
+                    task.map(Message::Channel)
+                }
+                None => Task::none(),
+            },
+            Message::EnqueuePressed(index) => {
+                let Some(SearchItem::Video(video)) = self.results.get(index).cloned() else {
+                    return Task::none();
+                };
+
+                let Backend::Started(backend) = &self.backend else {
+                    return Task::none();
+                };
+
+                command(backend.clone().enqueue(video.into()))
+            }
+            Message::DownloadPressed(index) => {
+                let Some(SearchItem::Video(video)) = self.results.get(index).cloned() else {
                     return Task::none();
                 };
 
@@ -104,15 +312,106 @@ impl App {
                     return Task::none();
                 };
 
-                let (player, task) = Player::new(video.clone(), backend.clone());
-                self.player = Some(player);
+                self.failed_downloads.remove(&video.id);
+                self.downloading.insert(video.id.clone());
+
+                command(backend.clone().download(video))
+            }
+            Message::DownloadsPressed => {
+                self.is_showing_downloads = true;
+                self.settings = None;
+                self.equalizer = None;
+                self.library = None;
+
+                Task::none()
+            }
+            Message::Downloads(message) => match downloads::update(message, &self.downloads) {
+                downloads::Action::None => Task::none(),
+                downloads::Action::Play(video) => self.play(video.into()),
+                downloads::Action::Remove(id) => {
+                    let Backend::Started(backend) = &self.backend else {
+                        return Task::none();
+                    };
+
+                    command(backend.clone().remove_download(id))
+                }
+                downloads::Action::Back => {
+                    self.is_showing_downloads = false;
+
+                    Task::none()
+                }
+            },
+            Message::PlaylistLoaded(Ok(videos)) => {
+                self.play_all(videos.into_iter().map(Track::from).collect())
+            }
+            Message::PlaylistLoaded(Err(err)) => {
+                tracing::warn!("loading the playlist failed: {err}");
+                self.error = Some(err);
+
+                Task::none()
+            }
+            Message::LoginChecked(logged_in) => {
+                if logged_in {
+                    self.login = Login::LoggedIn;
+                }
+
+                Task::none()
+            }
+            Message::LoginPressed => {
+                let Ok(client) = &self.client else {
+                    return Task::none();
+                };
+
+                self.login = Login::Requesting;
+
+                Task::perform(
+                    client.clone().request_device_code(),
+                    Message::DeviceCodeReceived,
+                )
+            }
+            Message::DeviceCodeReceived(Ok(authorization)) => {
+                let Ok(client) = &self.client else {
+                    return Task::none();
+                };
+
+                self.login = Login::Pending(authorization.clone());
 
-                task.map(Message::Player)
+                Task::perform(client.clone().login(authorization), Message::LoggedIn)
+            }
+            Message::LoggedIn(Ok(())) => {
+                self.login = Login::LoggedIn;
+
+                Task::none()
+            }
+            Message::DeviceCodeReceived(Err(err)) | Message::LoggedIn(Err(err)) => {
+                tracing::warn!("logging in failed: {err}");
+                self.login = Login::LoggedOut;
+                self.error = Some(err);
+
+                Task::none()
+            }
+            Message::LogoutPressed => {
+                let Ok(client) = &self.client else {
+                    return Task::none();
+                };
+
+                self.login = Login::LoggedOut;
+
+                Task::perform(client.clone().logout(), Message::LoggedOut)
+            }
+            Message::LoggedOut(result) => {
+                if let Err(err) = result {
+                    tracing::warn!("logging out failed: {err}");
+                    self.error = Some(err);
+                }
+
+                Task::none()
             }
             Message::Backend(event) => match event {
                 backend::Event::Started(backend) => {
-                    self.backend = Backend::Started(backend);
-                    Task::none()
+                    self.backend = Backend::Started(backend.clone());
+
+                    self.restore_preferences(backend)
                 }
                 backend::Event::PlaybackPosition(pos) => {
                     let Some(player) = &mut self.player else {
@@ -121,9 +420,333 @@ impl App {
 
                     player.set_cur_pos(pos);
 
+                    Task::none()
+                }
+                backend::Event::TrackStarted(track, duration) => {
+                    match &mut self.player {
+                        Some(player) => player.track_started(track, duration),
+                        None => {
+                            let mut player = Player::playing(track, duration);
+                            player.set_volume(self.preferences.volume, self.preferences.muted);
+                            player.set_speed(self.preferences.speed);
+
+                            self.player = Some(player);
+                        }
+                    }
+
+                    Task::none()
+                }
+                backend::Event::QueueChanged(queue) => {
+                    self.queue = queue;
+
+                    Task::none()
+                }
+                backend::Event::TrackEnded => {
+                    if let Some(player) = &mut self.player {
+                        player.track_ended();
+                    }
+
+                    Task::none()
+                }
+                backend::Event::Buffering { progress } => {
+                    if let Some(player) = &mut self.player {
+                        player.buffering(progress);
+                    }
+
+                    Task::none()
+                }
+                backend::Event::Downloaded(fraction) => {
+                    if let Some(player) = &mut self.player {
+                        player.set_downloaded(fraction);
+                    }
+
+                    Task::none()
+                }
+                backend::Event::SeekFailed { position, error } => {
+                    tracing::warn!("seeking to {position:?} failed: {error}");
+
+                    if let Some(player) = &mut self.player {
+                        player.seek_failed(error);
+                    }
+
+                    Task::none()
+                }
+                backend::Event::Resumed => {
+                    if let Some(player) = &mut self.player {
+                        player.resumed();
+                    }
+
+                    Task::none()
+                }
+                backend::Event::VolumeChanged { volume, muted } => {
+                    if let Some(player) = &mut self.player {
+                        player.set_volume(volume, muted);
+                    }
+
+                    self.preferences.volume = volume;
+                    self.preferences.muted = muted;
+
+                    self.save_preferences()
+                }
+                backend::Event::OutputDeviceChanged(device) => {
+                    self.device_error = None;
+                    self.output_device_error = None;
+                    self.preferences.output_device = device;
+
+                    self.save_preferences()
+                }
+                backend::Event::CrossfadeChanged(duration) => {
+                    self.preferences.crossfade_secs = duration.as_secs_f32();
+
+                    self.save_preferences()
+                }
+                backend::Event::NormalizationChanged { enabled, target } => {
+                    self.preferences.normalize = enabled;
+                    self.preferences.target_loudness = target;
+
+                    self.save_preferences()
+                }
+                backend::Event::EqualizerChanged { enabled, gains } => {
+                    self.preferences.equalizer = enabled;
+                    self.preferences.equalizer_gains = gains;
+
+                    self.save_preferences()
+                }
+                backend::Event::SpeedChanged(speed) => {
+                    if let Some(player) = &mut self.player {
+                        player.set_speed(speed);
+                    }
+
+                    self.preferences.speed = speed;
+
+                    self.save_preferences()
+                }
+                backend::Event::DownloadsChanged(downloads) => {
+                    self.downloading
+                        .retain(|id| !downloads.iter().any(|video| &video.id == id));
+                    self.downloads = downloads;
+
+                    Task::none()
+                }
+                backend::Event::DownloadFailed { id, error } => {
+                    tracing::warn!("downloading {id} failed: {error}");
+                    self.downloading.remove(&id);
+                    self.failed_downloads.insert(id, error);
+
+                    Task::none()
+                }
+                backend::Event::CacheSizeChanged(size) => {
+                    self.preferences.cache_size = size;
+
+                    self.save_preferences()
+                }
+                backend::Event::FormatChanged(format) => {
+                    self.preferences.quality = format.quality;
+                    self.preferences.data_saver = format.data_saver;
+
+                    self.save_preferences()
+                }
+                backend::Event::OutputDeviceFailed { device, error } => {
+                    tracing::warn!(
+                        "switching to {} failed: {error}",
+                        device.as_deref().unwrap_or("the default device")
+                    );
+                    self.output_device_error = Some(error);
+
+                    Task::none()
+                }
+                backend::Event::DeviceUnavailable(err) => {
+                    tracing::warn!("audio output unavailable: {err}");
+                    self.device_error = Some(err);
+
+                    Task::none()
+                }
+                backend::Event::Failed(err) => {
+                    tracing::warn!("playback failed: {err}");
+
+                    match &mut self.player {
+                        Some(player) => player.failed(err),
+                        None => self.error = Some(err),
+                    }
+
                     Task::none()
                 }
             },
+            Message::Channel(message) => {
+                let Some(page) = &mut self.channel else {
+                    return Task::none();
+                };
+
+                match page.update(message) {
+                    channel::Action::None => Task::none(),
+                    channel::Action::Play(video) => self.play(video.into()),
+                    channel::Action::PlayPlaylist(playlist) => self.play_playlist(playlist),
+                    channel::Action::Back => {
+                        self.channel = None;
+
+                        Task::none()
+                    }
+                    channel::Action::Run(task) => task.map(Message::Channel),
+                }
+            }
+            Message::OpenFilePressed => Task::perform(library::pick_files(), Message::FilesPicked),
+            Message::FilesPicked(files) => {
+                self.play_all(files.into_iter().map(Track::from).collect())
+            }
+            Message::LibraryPressed => {
+                self.settings = None;
+                self.equalizer = None;
+
+                self.open_library()
+            }
+            Message::Library(message) => {
+                let Some(page) = &mut self.library else {
+                    return Task::none();
+                };
+
+                match page.update(message) {
+                    library::Action::None => Task::none(),
+                    library::Action::Play(file) => self.play(file.into()),
+                    library::Action::Enqueue(file) => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().enqueue(file.into()))
+                    }
+                    library::Action::PlayAll(files) => {
+                        self.play_all(files.into_iter().map(Track::from).collect())
+                    }
+                    library::Action::ChooseFolder => {
+                        Task::perform(library::pick_folder(), Message::LibraryFolderPicked)
+                    }
+                    library::Action::Rescan => self.open_library(),
+                    library::Action::Back => {
+                        self.library = None;
+
+                        Task::none()
+                    }
+                }
+            }
+            Message::LibraryFolderPicked(folder) => {
+                let Some(folder) = folder else {
+                    return Task::none();
+                };
+
+                self.preferences.library_folder = Some(folder);
+
+                Task::batch([self.save_preferences(), self.open_library()])
+            }
+            Message::SettingsPressed => {
+                self.settings = Some(settings::Page::new());
+                self.equalizer = None;
+
+                Task::none()
+            }
+            Message::Settings(message) => {
+                let Some(page) = &mut self.settings else {
+                    return Task::none();
+                };
+
+                match page.update(message, &self.preferences) {
+                    settings::Action::None => Task::none(),
+                    settings::Action::SelectDevice(device) => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_output_device(device))
+                    }
+                    settings::Action::SetCrossfade(duration) => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_crossfade(duration))
+                    }
+                    settings::Action::SetNormalization { enabled, target } => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_normalization(enabled, target))
+                    }
+                    settings::Action::SetCacheSize(size) => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_cache_size(size))
+                    }
+                    settings::Action::SetSearchProvider(provider) => {
+                        self.preferences.search_provider = provider;
+
+                        self.save_preferences()
+                    }
+                    settings::Action::SetInstanceUrl(url) => {
+                        match self.preferences.search_provider {
+                            ProviderKind::YouTube => {}
+                            ProviderKind::Invidious => self.preferences.invidious_url = url,
+                            ProviderKind::Piped => self.preferences.piped_url = url,
+                        }
+
+                        self.save_preferences()
+                    }
+                    settings::Action::SetFormat(format) => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_format(format))
+                    }
+                    settings::Action::Back => {
+                        self.settings = None;
+
+                        Task::none()
+                    }
+                }
+            }
+            Message::EqualizerPressed => {
+                self.equalizer = Some(equalizer::Panel::default());
+                self.settings = None;
+
+                Task::none()
+            }
+            Message::Equalizer(message) => {
+                let Some(panel) = &mut self.equalizer else {
+                    return Task::none();
+                };
+
+                match panel.update(message, &self.preferences) {
+                    equalizer::Action::None => Task::none(),
+                    equalizer::Action::SetEqualizer { enabled, gains } => {
+                        let Backend::Started(backend) = &self.backend else {
+                            return Task::none();
+                        };
+
+                        command(backend.clone().set_equalizer(enabled, gains))
+                    }
+                    equalizer::Action::SavePreset(preset) => {
+                        let presets = &mut self.preferences.presets;
+                        presets.retain(|saved| saved.name != preset.name);
+                        presets.push(preset);
+
+                        self.save_preferences()
+                    }
+                    equalizer::Action::DeletePreset(name) => {
+                        self.preferences
+                            .presets
+                            .retain(|preset| preset.name != name);
+
+                        self.save_preferences()
+                    }
+                    equalizer::Action::Back => {
+                        self.equalizer = None;
+
+                        Task::none()
+                    }
+                }
+            }
             Message::Player(message) => {
                 let Some(player) = &mut self.player else {
                     return Task::none();
@@ -135,7 +758,147 @@ impl App {
 
                 player.update(message, backend.clone()).map(Message::Player)
             }
+            Message::CommandFailed(err) => {
+                tracing::warn!("backend command failed: {err}");
+                self.command_error = Some(err);
+
+                Task::none()
+            }
+            Message::Queue(message) => {
+                let Backend::Started(backend) = &self.backend else {
+                    return Task::none();
+                };
+
+                command(queue::update(message, backend.clone()))
+            }
+        }
+    }
+
+    /// Appends the next page of results, if there is one.
+    fn load_more(&mut self) -> Task<Message> {
+        if self.is_loading_more {
+            return Task::none();
+        }
+
+        // Keep the cursor until the page arrived, so a failed request can be retried.
+        let Some(cursor) = self.next_page.clone() else {
+            return Task::none();
+        };
+
+        self.is_loading_more = true;
+        let generation = self.search_generation;
+
+        Task::perform(cursor.fetch(), move |result| {
+            Message::MoreVideosListed(generation, result)
+        })
+    }
+
+    /// The search provider chosen in the settings.
+    fn search_provider(&self) -> Result<Arc<dyn SearchProvider>, Error> {
+        let provider: Arc<dyn SearchProvider> = match self.preferences.search_provider {
+            ProviderKind::YouTube => Arc::new(self.client.clone()?),
+            ProviderKind::Invidious => Arc::new(Invidious::new(&self.preferences.invidious_url)),
+            ProviderKind::Piped => Arc::new(Piped::new(&self.preferences.piped_url)),
+        };
+
+        Ok(provider)
+    }
+
+    /// Saves the preferences once they stopped changing for a moment, e.g. after a slider is released.
+    fn save_preferences(&mut self) -> Task<Message> {
+        self.preferences_temperature += 1;
+
+        Task::perform(tokio::time::sleep(Duration::from_millis(500)), |_| {
+            Message::PreferencesCooled
+        })
+    }
+
+    fn flush_preferences(&mut self) {
+        self.preferences_temperature = 0;
+
+        if let Err(err) = self.preferences.save() {
+            tracing::error!("saving the preferences failed: {err}");
+        }
+    }
+
+    /// Applies the preferences of the last session to a freshly started backend.
+    fn restore_preferences(&self, backend: player_core::Backend) -> Task<Message> {
+        let preferences = self.preferences.clone();
+        // The preferences file may be edited by hand, a negative or NaN value disables it.
+        let crossfade = Duration::try_from_secs_f32(preferences.crossfade_secs).unwrap_or_default();
+
+        command(async move {
+            backend.clone().set_volume(preferences.volume).await?;
+            backend.clone().set_muted(preferences.muted).await?;
+            backend.clone().set_crossfade(crossfade).await?;
+            backend
+                .clone()
+                .set_normalization(preferences.normalize, preferences.target_loudness)
+                .await?;
+            backend
+                .clone()
+                .set_equalizer(preferences.equalizer, preferences.equalizer_gains)
+                .await?;
+            backend.clone().set_speed(preferences.speed).await?;
+            backend
+                .clone()
+                .set_cache_size(preferences.cache_size)
+                .await?;
+            backend
+                .clone()
+                .set_format(Format {
+                    quality: preferences.quality,
+                    data_saver: preferences.data_saver,
+                })
+                .await?;
+
+            match preferences.output_device {
+                Some(device) => backend.set_output_device(Some(device)).await,
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn play(&mut self, track: Track) -> Task<Message> {
+        let Backend::Started(backend) = &self.backend else {
+            return Task::none();
+        };
+
+        let (mut player, task) = Player::new(track, backend.clone());
+        player.set_volume(self.preferences.volume, self.preferences.muted);
+        player.set_speed(self.preferences.speed);
+
+        self.player = Some(player);
+
+        task.map(Message::Player)
+    }
+
+    fn play_all(&mut self, tracks: Vec<Track>) -> Task<Message> {
+        let Backend::Started(backend) = &self.backend else {
+            return Task::none();
+        };
+
+        if tracks.is_empty() {
+            return Task::none();
         }
+
+        command(backend.clone().play_all(tracks))
+    }
+
+    /// Shows the library page, scanning the library folder again.
+    fn open_library(&mut self) -> Task<Message> {
+        let (page, task) = library::Page::new(self.preferences.library_folder.clone());
+        self.library = Some(page);
+
+        task.map(Message::Library)
+    }
+
+    fn play_playlist(&mut self, playlist: Playlist) -> Task<Message> {
+        let Ok(client) = &self.client else {
+            return Task::none();
+        };
+
+        Task::perform(playlist.items(client.clone()), Message::PlaylistLoaded)
     }
 
     fn view(&self) -> Element<'_, Message> {
@@ -144,37 +907,165 @@ impl App {
             .padding(10)
             .on_input(Message::SearchChanged);
 
-        let content = if self.is_searching || self.search_temperature > 0 {
-            container(text("Searching...")).center(Length::Fill)
+        let login: Element<_> = match &self.login {
+            Login::LoggedOut => button("Sign in").on_press(Message::LoginPressed).into(),
+            Login::Requesting => text("Signing in...").into(),
+            Login::Pending(authorization) => text!(
+                "Open {} and enter {}",
+                authorization.verification_url,
+                authorization.user_code
+            )
+            .into(),
+            Login::LoggedIn => button("Sign out")
+                .on_press(Message::LogoutPressed)
+                .style(button::secondary)
+                .into(),
+        };
+
+        let settings = button("Settings")
+            .on_press(Message::SettingsPressed)
+            .style(button::secondary);
+
+        let equalizer = button("Equalizer")
+            .on_press(Message::EqualizerPressed)
+            .style(button::secondary);
+
+        let open_file = button("Open file")
+            .on_press(Message::OpenFilePressed)
+            .style(button::secondary);
+
+        let library = button("Library")
+            .on_press(Message::LibraryPressed)
+            .style(button::secondary);
+
+        let downloads = button("Downloads")
+            .on_press(Message::DownloadsPressed)
+            .style(button::secondary);
+
+        let header = row![
+            search, open_file, library, downloads, equalizer, settings, login
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let content: Element<_> = if let Some(panel) = &self.equalizer {
+            panel.view(&self.preferences).map(Message::Equalizer)
+        } else if let Some(page) = &self.settings {
+            page.view(&self.preferences, self.output_device_error.as_ref())
+                .map(Message::Settings)
+        } else if self.is_showing_downloads {
+            downloads::view(&self.downloads).map(Message::Downloads)
+        } else if let Some(page) = &self.library {
+            page.view().map(Message::Library)
+        } else if let Some(page) = &self.channel {
+            page.view().map(Message::Channel)
+        } else if self.is_searching || self.search_temperature > 0 {
+            container(text("Searching...")).center(Length::Fill).into()
+        } else if let Some(error) = &self.error {
+            container(text(error.to_string()))
+                .center(Length::Fill)
+                .into()
         } else {
-            if self.videos.is_empty() {
-                container(text("No videos found!")).center(Length::Fill)
+            if self.results.is_empty() {
+                container(text("No videos found!"))
+                    .center(Length::Fill)
+                    .into()
             } else {
-                let list = scrollable(
-                    column(self.videos.iter().enumerate().map(|(i, v)| {
-                        button(
+                let mut results = column(self.results.iter().enumerate().map(|(i, item)| {
+                    let row = match item {
+                        SearchItem::Video(v) => {
+                            let mut details = Vec::new();
+
+                            if let Some(count) = v.view_count {
+                                details.push(format!("{count} views"));
+                            }
+                            if let Some(published_at) = v.published_at {
+                                details.push(published_at.format("%Y-%m-%d").to_string());
+                            }
+                            details.push(format!(
+                                "{:02}:{:02}:{:02}",
+                                v.duration.num_hours(),
+                                v.duration.num_minutes() % 60,
+                                v.duration.num_seconds() % 60
+                            ));
+
                             row![
                                 text(&v.title),
+                                text(&v.channel_title).style(text::secondary),
                                 horizontal_space(),
-                                text!(
-                                    "{:02}:{:02}:{:02}",
-                                    v.duration.num_hours(),
-                                    v.duration.num_minutes() % 60,
-                                    v.duration.num_seconds() % 60
-                                )
+                                text(details.join(" · "))
                             ]
-                            .align_y(Alignment::Center),
-                        )
-                        .on_press(Message::VideoSelected(i))
+                        }
+                        SearchItem::Playlist(p) => row![
+                            text(&p.title),
+                            text(&p.channel_title).style(text::secondary),
+                            horizontal_space(),
+                            text!("Playlist · {} videos", p.item_count)
+                        ],
+                        SearchItem::Channel(c) => row![
+                            text(&c.title),
+                            horizontal_space(),
+                            text(match c.subscriber_count {
+                                Some(count) => format!("Channel · {count} subscribers"),
+                                None => "Channel".to_string(),
+                            })
+                        ],
+                    };
+
+                    let result = button(row.spacing(10).align_y(Alignment::Center))
+                        .on_press(Message::ResultSelected(i))
                         .width(Length::Fill)
-                        .style(button::secondary)
-                        .into()
-                    }))
-                    .spacing(5),
-                )
+                        .style(button::secondary);
+
+                    match item {
+                        SearchItem::Video(video) => row![
+                            result,
+                            button("Queue")
+                                .on_press(Message::EnqueuePressed(i))
+                                .style(button::text),
+                            self.download_button(i, video)
+                        ]
+                        .spacing(5)
+                        .align_y(Alignment::Center)
+                        .into(),
+                        _ => result.into(),
+                    }
+                }))
                 .spacing(5);
 
-                container(list).center(Length::Fill)
+                if self.is_loading_more {
+                    results =
+                        results.push(container(text("Loading more...")).center_x(Length::Fill));
+                } else if let Some(err) = &self.page_error {
+                    results = results.push(
+                        container(
+                            row![
+                                text(err.to_string()).style(text::danger),
+                                button("Retry")
+                                    .on_press(Message::RetryPagePressed)
+                                    .style(button::secondary)
+                            ]
+                            .spacing(10)
+                            .align_y(Alignment::Center),
+                        )
+                        .center_x(Length::Fill),
+                    );
+                }
+
+                let list = scrollable(results)
+                    .spacing(5)
+                    .on_scroll(|viewport| Message::ResultsScrolled(viewport.relative_offset().y));
+
+                column![
+                    text(match self.total_results {
+                        Some(total) => format!("{} of {total} results", self.results.len()),
+                        None => format!("{} results", self.results.len()),
+                    })
+                    .size(12),
+                    container(list).center(Length::Fill)
+                ]
+                .spacing(5)
+                .into()
             }
         };
 
@@ -183,11 +1074,66 @@ impl App {
             None => container(text("Choose a file to start playback.")).into(),
         };
 
-        container(column![search, content, horizontal_rule(1), player].spacing(10))
+        let content = row![
+            container(content).width(Length::FillPortion(3)),
+            container(queue::view(&self.queue).map(Message::Queue)).width(Length::FillPortion(1))
+        ]
+        .spacing(10)
+        .height(Length::Fill);
+
+        let mut layout = column![header].spacing(10);
+
+        if let Some(err) = &self.command_error {
+            layout = layout.push(
+                container(text!("Playback is unavailable: {err}").style(text::danger))
+                    .padding(5)
+                    .width(Length::Fill)
+                    .style(container::bordered_box),
+            );
+        }
+
+        if let Some(err) = &self.device_error {
+            layout = layout.push(
+                container(text!("No audio output: {err}. Retrying...").style(text::danger))
+                    .padding(5)
+                    .width(Length::Fill)
+                    .style(container::bordered_box),
+            );
+        }
+
+        container(layout.push(content).push(horizontal_rule(1)).push(player))
             .padding(10)
             .into()
     }
 
+    fn download_button(&self, index: usize, video: &Video) -> Element<'_, Message> {
+        if self
+            .downloads
+            .iter()
+            .any(|download| download.id == video.id)
+        {
+            text("Downloaded").style(text::secondary).into()
+        } else if self.downloading.contains(&video.id) {
+            text("Downloading...").style(text::secondary).into()
+        } else if let Some(err) = self.failed_downloads.get(&video.id) {
+            tooltip(
+                button(text("Download failed, retry").style(text::danger))
+                    .on_press(Message::DownloadPressed(index))
+                    .style(button::text),
+                container(text(err.to_string()))
+                    .padding(5)
+                    .style(container::bordered_box),
+                tooltip::Position::Bottom,
+            )
+            .into()
+        } else {
+            button("Download")
+                .on_press(Message::DownloadPressed(index))
+                .style(button::text)
+                .into()
+        }
+    }
+
     fn subscription(&self) -> Subscription<Message> {
         Subscription::run(backend::start).map(Message::Backend)
     }
@@ -196,3 +1142,16 @@ impl App {
         Theme::TokyoNightStorm
     }
 }
+
+impl Drop for App {
+    fn drop(&mut self) {
+        if self.preferences_temperature > 0 {
+            self.flush_preferences();
+        }
+    }
+}
+
+/// Runs a backend command, reporting it if it didn't reach the player.
+fn command(command: impl Future<Output = Result<(), Error>> + Send + 'static) -> Task<Message> {
+    Task::perform(command, Result::err).and_then(|err| Task::done(Message::CommandFailed(err)))
+}
@@ -2,85 +2,240 @@ use std::time::Duration;
 
 use iced::{
     Alignment, Element, Length, Task,
-    widget::{button, column, container, horizontal_space, row, slider, text},
+    widget::{button, column, container, horizontal_rule, horizontal_space, row, slider, text},
 };
-use player_core::{Backend, Video};
+use player_core::backend::{MAX_SPEED, MIN_SPEED};
+use player_core::{Backend, Error, Track};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     PausePressed,
     PlayPressed,
     PlayheadMoved(f32),
-    PlaybackStarted(Video, Duration, Duration),
+    PlaybackStarted(Track, Duration, Duration),
+    LoadFailed(Error),
+    RetryPressed,
     VideoPaused,
+    NextPressed,
+    PreviousPressed,
+    VolumeChanged(f32),
+    MuteToggled,
+    SpeedChanged(f32),
+    CommandFailed(Error),
 }
 
 pub struct Player {
-    video: Video,
+    track: Track,
     cur_pos: Duration,
     duration: Duration,
+    /// The downloaded fraction of the track, shown below the playhead.
+    downloaded: f32,
     state: State,
+    /// The last playback error, shown until the next track starts.
+    error: Option<Error>,
+    volume: f32,
+    muted: bool,
+    speed: f32,
 }
 
 enum State {
     Loading,
+    Buffering(f32),
     Playing,
     Pause,
+    Ended,
+    /// Loading the track failed, it can be retried.
+    Failed,
 }
 
 impl Player {
-    pub fn new(video: Video, backend: Backend) -> (Self, Task<Message>) {
+    pub fn new(track: Track, backend: Backend) -> (Self, Task<Message>) {
         (
             Self {
-                video: video.clone(),
+                track: track.clone(),
                 cur_pos: Duration::from_secs(0),
-                duration: video.duration.to_std().unwrap(),
+                duration: track.duration().unwrap_or_default(),
+                downloaded: 0.0,
                 state: State::Loading,
+                error: None,
+                volume: 1.0,
+                muted: false,
+                speed: 1.0,
+            },
+            Self::load(track, backend),
+        )
+    }
+
+    fn load(track: Track, backend: Backend) -> Task<Message> {
+        Task::perform(
+            backend.load_and_play(track.clone()),
+            move |result| match result {
+                Ok(info) => Message::PlaybackStarted(
+                    track.clone(),
+                    Duration::from_secs(0),
+                    info.duration
+                        .or_else(|| track.duration())
+                        .unwrap_or_default(),
+                ),
+                Err(err) => Message::LoadFailed(err),
             },
-            Task::perform(backend.load_and_play(video.id.clone()), move |duration| {
-                Message::PlaybackStarted(video, Duration::from_secs(0), duration.unwrap())
-            }),
         )
     }
 
+    /// Creates the player for a track the backend started on its own, e.g.
+    /// when advancing the queue.
+    pub fn playing(track: Track, duration: Option<Duration>) -> Self {
+        let mut player = Self {
+            track: track.clone(),
+            cur_pos: Duration::from_secs(0),
+            duration: Duration::from_secs(0),
+            downloaded: 0.0,
+            state: State::Playing,
+            error: None,
+            volume: 1.0,
+            muted: false,
+            speed: 1.0,
+        };
+        player.track_started(track, duration);
+
+        player
+    }
+
+    pub fn track_started(&mut self, track: Track, duration: Option<Duration>) {
+        self.duration = duration.or_else(|| track.duration()).unwrap_or_default();
+        self.track = track;
+        self.cur_pos = Duration::from_secs(0);
+        self.downloaded = 0.0;
+        self.state = State::Playing;
+        self.error = None;
+    }
+
+    pub fn track_ended(&mut self) {
+        self.cur_pos = self.duration;
+        self.state = State::Ended;
+    }
+
+    pub fn buffering(&mut self, progress: f32) {
+        self.state = State::Buffering(progress);
+    }
+
+    pub fn resumed(&mut self) {
+        self.state = State::Playing;
+    }
+
+    /// A track the backend loaded on its own failed, the backend moves on
+    /// to the next one if there is any.
+    pub fn failed(&mut self, error: Error) {
+        self.state = State::Ended;
+        self.error = Some(error);
+    }
+
+    pub fn set_downloaded(&mut self, downloaded: f32) {
+        self.downloaded = downloaded;
+    }
+
+    /// Playback goes on where it was, the next position report moves the
+    /// playhead back.
+    pub fn seek_failed(&mut self, error: Error) {
+        self.error = Some(error);
+    }
+
+    pub fn set_volume(&mut self, volume: f32, muted: bool) {
+        self.volume = volume;
+        self.muted = muted;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
     pub fn update(&mut self, message: Message, backend: Backend) -> Task<Message> {
         match message {
+            Message::PlayheadMoved(_) if self.duration.is_zero() => Task::none(),
             Message::PlayheadMoved(pos) => {
                 let new_pos = pos * self.duration.as_secs_f32();
                 self.cur_pos = Duration::from_secs_f32(new_pos);
 
-                Task::perform(backend.seek_to(Duration::from_secs_f32(new_pos)), |_| {}).discard()
+                command(backend.seek_to(Duration::from_secs_f32(new_pos)))
             }
-            Message::PlaybackStarted(video, cur_pos, duration) => {
-                self.video = video;
+            Message::PlaybackStarted(track, cur_pos, duration) => {
+                self.track = track;
 
                 self.cur_pos = cur_pos;
                 self.duration = duration;
 
                 self.state = State::Playing;
+                self.error = None;
+
+                Task::none()
+            }
+            Message::LoadFailed(err) => {
+                self.state = State::Failed;
+                tracing::warn!("loading {} failed: {err}", self.track.title());
+                self.error = Some(err);
 
                 Task::none()
             }
-            Message::PausePressed => Task::perform(backend.pause(), |_| Message::VideoPaused),
+            Message::RetryPressed => {
+                self.state = State::Loading;
+                self.error = None;
+
+                Self::load(self.track.clone(), backend)
+            }
+            Message::PausePressed => Task::perform(backend.pause(), |result| match result {
+                Ok(()) => Message::VideoPaused,
+                Err(err) => Message::CommandFailed(err),
+            }),
             Message::VideoPaused => {
                 self.state = State::Pause;
 
                 Task::none()
             }
+            Message::NextPressed => command(backend.skip()),
+            Message::PreviousPressed => command(backend.previous()),
+            Message::VolumeChanged(volume) => {
+                self.volume = volume;
+
+                command(backend.set_volume(volume))
+            }
+            Message::MuteToggled => command(backend.set_muted(!self.muted)),
+            Message::SpeedChanged(speed) => {
+                self.speed = speed;
+
+                command(backend.set_speed(speed))
+            }
+            Message::PlayPressed if matches!(self.state, State::Ended) => {
+                // The backend loads the track again.
+                self.state = State::Loading;
+
+                command(backend.play())
+            }
             Message::PlayPressed => {
-                let video = self.video.clone();
+                let track = self.track.clone();
                 let cur_pos = self.cur_pos;
                 let duration = self.duration;
 
-                Task::perform(backend.play(), move |_| {
-                    Message::PlaybackStarted(video, cur_pos, duration)
+                Task::perform(backend.play(), move |result| match result {
+                    Ok(()) => Message::PlaybackStarted(track, cur_pos, duration),
+                    Err(err) => Message::CommandFailed(err),
                 })
             }
+            Message::CommandFailed(err) => {
+                tracing::warn!("player command failed: {err}");
+                self.error = Some(err);
+
+                Task::none()
+            }
         }
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        let normalized_pos = self.cur_pos.as_secs_f32() / self.duration.as_secs_f32();
+        // Local files may not tell their length, their progress is unknown then.
+        let normalized_pos = if self.duration.is_zero() {
+            0.0
+        } else {
+            (self.cur_pos.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
 
         let format_time = |time: &Duration| {
             let secs = time.as_secs();
@@ -96,28 +251,109 @@ impl Player {
             }
         };
 
-        let controls: Element<_> = match self.state {
+        let controls: Element<_> = match &self.state {
             State::Loading => text("Loading...").into(),
+            State::Buffering(progress) => row![
+                button("Pause").on_press(Message::PausePressed),
+                text!("Buffering {:.0}%", progress * 100.0).style(text::secondary)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into(),
             State::Playing => button("Pause").on_press(Message::PausePressed).into(),
-            State::Pause => button("Play").on_press(Message::PlayPressed).into(),
+            State::Pause | State::Ended => button("Play").on_press(Message::PlayPressed).into(),
+            State::Failed => button("Retry").on_press(Message::RetryPressed).into(),
+        };
+
+        let controls: Element<_> = match &self.error {
+            Some(err) => column![controls, text(err.to_string()).style(text::danger)]
+                .spacing(5)
+                .align_x(Alignment::Center)
+                .into(),
+            None => controls,
+        };
+
+        // Positions are within the track, but it takes less or more time to
+        // play at another speed.
+        let duration: Element<_> = if self.duration.is_zero() {
+            column![].into()
+        } else if self.speed == 1.0 {
+            text(format_time(&self.duration)).into()
+        } else {
+            let remaining = self
+                .duration
+                .saturating_sub(self.cur_pos)
+                .div_f32(self.speed);
+
+            column![
+                text(format_time(&self.duration)),
+                text!("{} left", format_time(&remaining))
+                    .size(12)
+                    .style(text::secondary)
+            ]
+            .align_x(Alignment::End)
+            .into()
+        };
+
+        // Seeking within the downloaded part is instant, further ahead it
+        // waits for the download.
+        let downloaded = {
+            let parts = (self.downloaded.clamp(0.0, 1.0) * 1000.0) as u16;
+
+            row![
+                container(horizontal_rule(2)).width(Length::FillPortion(parts.max(1))),
+                horizontal_space().width(Length::FillPortion((1000 - parts).max(1)))
+            ]
         };
 
         container(
             row![
-                text(&self.video.title).width(Length::FillPortion(1)),
+                text(self.track.title()).width(Length::FillPortion(1)),
                 column![
-                    container(controls).center_x(Length::Fill),
+                    container(
+                        row![
+                            button("Previous")
+                                .on_press(Message::PreviousPressed)
+                                .style(button::secondary),
+                            controls,
+                            button("Next")
+                                .on_press(Message::NextPressed)
+                                .style(button::secondary)
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                    )
+                    .center_x(Length::Fill),
                     row![
                         text(format_time(&self.cur_pos)),
-                        slider(0.0..=1.0, normalized_pos, Message::PlayheadMoved).step(0.01),
-                        text(format_time(&self.duration))
+                        column![
+                            slider(0.0..=1.0, normalized_pos, Message::PlayheadMoved).step(0.01),
+                            downloaded
+                        ]
+                        .spacing(2),
+                        duration
                     ]
                     .spacing(10)
                     .align_y(Alignment::Center)
                 ]
                 .spacing(10)
                 .width(Length::FillPortion(1)),
-                horizontal_space()
+                row![
+                    horizontal_space(),
+                    text!("{}x", self.speed),
+                    slider(MIN_SPEED..=MAX_SPEED, self.speed, Message::SpeedChanged)
+                        .step(0.25)
+                        .width(80),
+                    button(if self.muted { "Unmute" } else { "Mute" })
+                        .on_press(Message::MuteToggled)
+                        .style(button::text),
+                    slider(0.0..=1.0, self.volume, Message::VolumeChanged)
+                        .step(0.01)
+                        .width(120)
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .width(Length::FillPortion(1))
             ]
             .spacing(10)
             .align_y(Alignment::Center),
@@ -130,3 +366,10 @@ impl Player {
         self.cur_pos = pos;
     }
 }
+
+/// Runs a backend command, showing its error in the player.
+fn command<T: Send + 'static>(
+    command: impl Future<Output = Result<T, Error>> + Send + 'static,
+) -> Task<Message> {
+    Task::perform(command, Result::err).and_then(|err| Task::done(Message::CommandFailed(err)))
+}
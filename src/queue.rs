@@ -0,0 +1,65 @@
+use iced::{
+    Alignment, Element, Length,
+    widget::{button, column, container, row, scrollable, text},
+};
+use player_core::{Backend, Error, backend::Queue};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    TrackSelected(usize),
+    RemovePressed(usize),
+    MoveUpPressed(usize),
+    MoveDownPressed(usize),
+}
+
+pub async fn update(message: Message, backend: Backend) -> Result<(), Error> {
+    match message {
+        Message::TrackSelected(index) => backend.jump_to(index).await,
+        Message::RemovePressed(index) => backend.remove(index).await,
+        Message::MoveUpPressed(index) => backend.reorder(index, index.saturating_sub(1)).await,
+        Message::MoveDownPressed(index) => backend.reorder(index, index + 1).await,
+    }
+}
+
+pub fn view(queue: &Queue) -> Element<'_, Message> {
+    let content: Element<_> = if queue.is_empty() {
+        container(text("The queue is empty."))
+            .center(Length::Fill)
+            .into()
+    } else {
+        let tracks = column(queue.tracks().iter().enumerate().map(|(i, track)| {
+            let is_current = queue.current() == Some(i);
+
+            row![
+                button(text(track.title()))
+                    .on_press(Message::TrackSelected(i))
+                    .width(Length::Fill)
+                    .style(if is_current {
+                        button::primary
+                    } else {
+                        button::secondary
+                    }),
+                button("Up")
+                    .on_press_maybe((i > 0).then_some(Message::MoveUpPressed(i)))
+                    .style(button::text),
+                button("Down")
+                    .on_press_maybe((i + 1 < queue.len()).then_some(Message::MoveDownPressed(i)))
+                    .style(button::text),
+                button("Remove")
+                    .on_press(Message::RemovePressed(i))
+                    .style(button::text),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(5);
+
+        scrollable(tracks).spacing(5).into()
+    };
+
+    column![text!("Queue · {} tracks", queue.len()).size(16), content]
+        .spacing(10)
+        .width(Length::Fill)
+        .into()
+}
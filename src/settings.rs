@@ -0,0 +1,261 @@
+use std::fmt;
+use std::time::Duration;
+
+use iced::{
+    Alignment, Element, Length,
+    widget::{
+        button, column, container, pick_list, row, scrollable, slider, text, text_input, toggler,
+    },
+};
+use player_core::backend::{Format, Quality};
+use player_core::{Error, Preferences, ProviderKind, backend};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    DeviceSelected(Device),
+    RefreshPressed,
+    CrossfadeChanged(f32),
+    NormalizeToggled(bool),
+    TargetLoudnessChanged(f32),
+    CacheSizeChanged(f32),
+    QualitySelected(Quality),
+    DataSaverToggled(bool),
+    SearchProviderSelected(ProviderKind),
+    InstanceUrlChanged(String),
+    BackPressed,
+}
+
+/// What the settings page asks the app to do.
+pub enum Action {
+    None,
+    SelectDevice(Option<String>),
+    SetCrossfade(Duration),
+    SetNormalization { enabled: bool, target: f32 },
+    SetCacheSize(u64),
+    SetFormat(Format),
+    SetSearchProvider(ProviderKind),
+    SetInstanceUrl(String),
+    Back,
+}
+
+const GIGABYTE: f32 = 1024.0 * 1024.0 * 1024.0;
+
+pub struct Page {
+    devices: Result<Vec<Device>, Error>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Device {
+    Default,
+    Named(String),
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("System default"),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Page {
+    pub fn new() -> Self {
+        Self {
+            devices: list_devices(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message, preferences: &Preferences) -> Action {
+        match message {
+            Message::DeviceSelected(Device::Default) => Action::SelectDevice(None),
+            Message::DeviceSelected(Device::Named(name)) => Action::SelectDevice(Some(name)),
+            Message::RefreshPressed => {
+                self.devices = list_devices();
+
+                Action::None
+            }
+            Message::CrossfadeChanged(secs) => Action::SetCrossfade(Duration::from_secs_f32(secs)),
+            Message::NormalizeToggled(enabled) => Action::SetNormalization {
+                enabled,
+                target: preferences.target_loudness,
+            },
+            Message::TargetLoudnessChanged(target) => Action::SetNormalization {
+                enabled: preferences.normalize,
+                target,
+            },
+            Message::CacheSizeChanged(gigabytes) => {
+                Action::SetCacheSize((gigabytes * GIGABYTE) as u64)
+            }
+            Message::QualitySelected(quality) => Action::SetFormat(Format {
+                quality,
+                data_saver: preferences.data_saver,
+            }),
+            Message::DataSaverToggled(data_saver) => Action::SetFormat(Format {
+                quality: preferences.quality,
+                data_saver,
+            }),
+            Message::SearchProviderSelected(provider) => Action::SetSearchProvider(provider),
+            Message::InstanceUrlChanged(url) => Action::SetInstanceUrl(url),
+            Message::BackPressed => Action::Back,
+        }
+    }
+
+    /// `device_error` tells why the chosen output device isn't played on.
+    pub fn view<'a>(
+        &'a self,
+        preferences: &'a Preferences,
+        device_error: Option<&'a Error>,
+    ) -> Element<'a, Message> {
+        let selected = match &preferences.output_device {
+            Some(name) => Device::Named(name.clone()),
+            None => Device::Default,
+        };
+
+        let devices: Element<_> = match &self.devices {
+            Ok(devices) => pick_list(devices.as_slice(), Some(selected), Message::DeviceSelected)
+                .width(Length::Fill)
+                .into(),
+            Err(err) => text(err.to_string()).style(text::danger).into(),
+        };
+
+        let mut output = column![
+            row![
+                devices,
+                button("Refresh")
+                    .on_press(Message::RefreshPressed)
+                    .style(button::secondary)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        ]
+        .spacing(5);
+
+        if let Some(err) = device_error {
+            output = output.push(text!("Switching failed: {err}").style(text::danger));
+        }
+
+        let output = section("Output device", output);
+
+        let crossfade = section(
+            "Crossfade",
+            row![
+                slider(
+                    0.0..=12.0,
+                    preferences.crossfade_secs,
+                    Message::CrossfadeChanged
+                )
+                .step(0.5),
+                text(if preferences.crossfade_secs > 0.0 {
+                    format!("{:.1} s", preferences.crossfade_secs)
+                } else {
+                    "Off".to_string()
+                })
+                .width(50)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+
+        let normalization = section(
+            "Loudness",
+            column![
+                toggler(preferences.normalize)
+                    .label("Normalize loudness across tracks")
+                    .on_toggle(Message::NormalizeToggled),
+                row![
+                    slider(
+                        -30.0..=-6.0,
+                        preferences.target_loudness,
+                        Message::TargetLoudnessChanged
+                    )
+                    .step(1.0),
+                    text!("{:.0} LUFS", preferences.target_loudness).width(80)
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center)
+            ]
+            .spacing(10),
+        );
+
+        let quality = section(
+            "Audio quality",
+            column![
+                pick_list(
+                    Quality::ALL,
+                    Some(preferences.quality),
+                    Message::QualitySelected
+                )
+                .width(Length::Fill),
+                toggler(preferences.data_saver)
+                    .label("Data saver, for metered connections")
+                    .on_toggle(Message::DataSaverToggled)
+            ]
+            .spacing(10),
+        );
+
+        let instance_url = match preferences.search_provider {
+            ProviderKind::YouTube => None,
+            ProviderKind::Invidious => Some(&preferences.invidious_url),
+            ProviderKind::Piped => Some(&preferences.piped_url),
+        };
+
+        let mut search = column![
+            pick_list(
+                ProviderKind::ALL,
+                Some(preferences.search_provider),
+                Message::SearchProviderSelected
+            )
+            .width(Length::Fill)
+        ]
+        .spacing(10);
+
+        if let Some(url) = instance_url {
+            search = search.push(
+                text_input("Instance URL, e.g. https://example.com", url)
+                    .on_input(Message::InstanceUrlChanged),
+            );
+        }
+
+        let search = section("Search", search);
+
+        let cache_size = preferences.cache_size as f32 / GIGABYTE;
+
+        let cache = section(
+            "Cache",
+            row![
+                slider(0.25..=20.0, cache_size, Message::CacheSizeChanged).step(0.25),
+                text!("{cache_size:.2} GB").width(80)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+
+        column![
+            row![
+                button("Back").on_press(Message::BackPressed),
+                text("Settings").size(20)
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            scrollable(
+                column![output, crossfade, normalization, quality, search, cache].spacing(20)
+            )
+            .height(Length::Fill)
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+fn section<'a>(title: &'a str, content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    container(column![text(title).size(16), content.into()].spacing(5)).into()
+}
+
+fn list_devices() -> Result<Vec<Device>, Error> {
+    let devices = backend::output_devices()?;
+
+    Ok(std::iter::once(Device::Default)
+        .chain(devices.into_iter().map(Device::Named))
+        .collect())
+}